@@ -0,0 +1,48 @@
+// Tree construction from flat file lists, as used for mdfind results and
+// partial trees. Throughput per file should stay flat as the tree grows.
+//
+//     cargo bench --bench tree
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use spacescout_lib::scanner::{
+    self,
+    size::{ByteSizes, SizeMetric},
+};
+
+// `count` files spread ten to a directory over a tree with a fan-out of ten,
+// so 1M files make 100k directories five levels deep
+fn synthetic_files(count: usize) -> HashMap<PathBuf, ByteSizes> {
+    (0..count)
+        .map(|i| {
+            let dir = i / 10;
+            let mut path = PathBuf::from("/bench");
+            let mut level = dir;
+            while level > 0 {
+                path.push(format!("d{}", level % 10));
+                level /= 10;
+            }
+            path.push(format!("f{}", i));
+            let size = (i as u64 * 7919) % 1_000_000;
+            (path, ByteSizes::new(size, size.next_multiple_of(4096)))
+        })
+        .collect()
+}
+
+fn build_tree(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_tree");
+    group.sample_size(10);
+    for count in [10_000, 100_000, 1_000_000] {
+        let files = synthetic_files(count);
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(count), &files, |b, files| {
+            b.iter_with_large_drop(|| scanner::tree::build_tree(black_box(files), Path::new("/bench"), SizeMetric::Allocated));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, build_tree);
+criterion_main!(benches);
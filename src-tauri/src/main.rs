@@ -3,15 +3,35 @@
     windows_subsystem = "windows"
 )]
 
-mod scanner;
-
-use scanner::{scan_directory, cancel_scan, FileNode};
-use std::path::Path;
+#[cfg(target_os = "linux")]
+use spacescout_lib::trash;
+use tauri::Manager;
+use spacescout_lib::scanner::{
+    backend::list_scan_backends,
+    error::ScanError,
+    manager::{cancel_scan, get_scan_status, list_scans, ScanManager},
+    options::ScanOptions,
+    paths::{self, PathArg},
+    scan_directory, ScanResult,
+};
 
 #[tauri::command]
-async fn scan_path(path: String, app_handle: tauri::AppHandle) -> Result<FileNode, String> {
-    let path = Path::new(&path);
-    scan_directory(path, &app_handle)
+async fn scan_path(
+    path: PathArg,
+    options: Option<ScanOptions>,
+    scan_id: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<ScanResult, ScanError> {
+    let path = path.into_path_buf()?;
+    // The walk blocks, so it runs on the blocking pool rather than tying up
+    // an async worker. scan_directory resolves the root itself.
+    let scan_root = path.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let manager = app_handle.state::<ScanManager>();
+        scan_directory(&scan_root, &options.unwrap_or_default(), scan_id, &manager, &app_handle)
+    })
+    .await
+    .map_err(|e| ScanError::other(&path, format!("Scan task failed: {}", e)))?
 }
 
 #[tauri::command]
@@ -22,64 +42,90 @@ fn get_home_directory() -> Result<String, String> {
 }
 
 #[tauri::command]
-fn show_in_finder(path: String) -> Result<(), String> {
+fn show_in_finder(path: PathArg) -> Result<(), ScanError> {
+    let path = paths::resolve_entry(&path.into_path_buf()?)?;
+    let path = path.as_path();
+    std::fs::symlink_metadata(path).map_err(|e| ScanError::io(path, &e))?;
+    
     #[cfg(target_os = "macos")]
     {
         use std::process::Command;
         
         Command::new("open")
             .arg("-R")
-            .arg(&path)
+            .arg(path)
             .spawn()
-            .map_err(|e| format!("Failed to open Finder: {}", e))?;
+            .map_err(|e| ScanError::io(path, &e))?;
         
         Ok(())
     }
     
     #[cfg(not(target_os = "macos"))]
     {
-        Err("Show in Finder is only available on macOS".to_string())
+        Err(ScanError::Unsupported {
+            path: path.to_string_lossy().to_string(),
+            code: None,
+            operation: "Show in Finder".to_string(),
+            backend: None,
+        })
     }
 }
 
 #[tauri::command]
-fn move_to_trash(path: String) -> Result<(), String> {
+fn move_to_trash(path: PathArg) -> Result<(), ScanError> {
+    let path = paths::resolve_entry(&path.into_path_buf()?)?;
+    let path = path.as_path();
+    std::fs::symlink_metadata(path).map_err(|e| ScanError::io(path, &e))?;
+    
     #[cfg(target_os = "macos")]
     {
         use std::process::Command;
         
-        // Use osascript to move file to trash
-        let script = format!(
-            r#"tell application "Finder" to delete POSIX file "{}" "#,
-            path
-        );
-        
+        // Finder moves the file to the trash. The path is handed to the
+        // script as an argument rather than spliced into its source, so no
+        // character in it can change the script.
         let output = Command::new("osascript")
-            .arg("-e")
-            .arg(&script)
+            .args(["-e", "on run argv"])
+            .args(["-e", r#"tell application "Finder" to delete POSIX file (item 1 of argv)"#])
+            .args(["-e", "end run"])
+            .arg(path)
             .output()
-            .map_err(|e| format!("Failed to execute AppleScript: {}", e))?;
+            .map_err(|e| ScanError::io(path, &e))?;
         
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("Failed to move to trash: {}", error));
+            return Err(ScanError::other(path, format!("Failed to move to trash: {}", error.trim())));
         }
         
         Ok(())
     }
     
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "linux")]
+    {
+        trash::move_to_trash(path)
+    }
+    
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
     {
-        Err("Move to Trash is only available on macOS".to_string())
+        Err(ScanError::Unsupported {
+            path: path.to_string_lossy().to_string(),
+            code: None,
+            operation: "Move to Trash".to_string(),
+            backend: None,
+        })
     }
 }
 
 fn main() {
     tauri::Builder::default()
+        .manage(ScanManager::default())
         .invoke_handler(tauri::generate_handler![
             scan_path,
             get_home_directory,
             cancel_scan,
+            list_scans,
+            get_scan_status,
+            list_scan_backends,
             show_in_finder,
             move_to_trash
         ])
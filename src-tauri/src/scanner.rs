@@ -1,55 +1,161 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU32, AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tauri::Emitter;
-use std::sync::Mutex;
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
-// Global scan state for cancellation
-static GLOBAL_SCAN_STATE: std::sync::OnceLock<Arc<Mutex<Option<Arc<AtomicBool>>>>> = std::sync::OnceLock::new();
+pub mod backend;
+mod backfill;
+mod cache;
+mod dir;
+mod du;
+mod dust;
+pub mod error;
+pub mod exclude;
+mod inodes;
+pub mod manager;
+mod limits;
+mod mdfind;
+mod mounts;
+mod native;
+mod node;
+pub mod options;
+pub mod paths;
+mod process;
+pub mod progress;
+pub mod size;
+pub mod tree;
 
-// Global file cache for seeding scans
-#[derive(Debug, Clone)]
-struct CachedFile {
-    path: PathBuf,
-    size: u64,
+use backend::{backend_for, default_backend, BackendKind};
+use backfill::Backfill;
+use error::{ScanError, SkippedPath};
+use exclude::{ExcludeMode, Exclusions};
+use manager::{ScanManager, ScanRegistration};
+use mounts::{ExcludedMount, Mount};
+pub use node::FileNode;
+use options::{HiddenEntries, ScanOptions, SymlinkPolicy};
+use process::ChildProcesses;
+use progress::{ScanCounters, ScanPhase, ScanProgress};
+use size::{ByteSizes, SizeMetric};
+
+// A panic on another walker thread leaves nothing half-updated in the
+// state's locks, so a poisoned one is used as is
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
 }
 
-static FILE_CACHE: std::sync::OnceLock<Arc<Mutex<Vec<CachedFile>>>> = std::sync::OnceLock::new();
+// Why a scan returned before walking everything
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IncompleteReason {
+    Cancelled,
+    TimeBudget,
+}
 
+// What scan_path returns; a cancelled or out-of-budget scan still carries the
+// best tree it had built
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct FileNode {
-    pub name: String,
-    pub path: String,
-    pub size: u64,
-    #[serde(rename = "isDir")]
-    pub is_dir: bool,
-    pub children: Option<Vec<FileNode>>,
+#[serde(rename_all = "camelCase")]
+pub struct ScanResult {
+    pub root: FileNode,
+    pub complete: bool,
+    pub incomplete_reason: Option<IncompleteReason>,
+    // Everything that could not be read, so a short total can be explained
+    pub skipped: Vec<SkippedPath>,
+    // Pseudo and network mounts the scan left out
+    pub excluded_mounts: Vec<ExcludedMount>,
 }
 
+// Payload of scan-intermediate events
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ScanProgress {
-    pub current_path: String,
-    pub items_processed: u32,
+pub struct ScanIntermediate {
+    #[serde(rename = "scanId")]
+    pub scan_id: String,
+    pub root: FileNode,
 }
 
-struct ScanState {
-    items_processed: Arc<AtomicU32>,
-    last_emit: Instant,
+pub struct ScanState {
+    id: String,
+    path: PathBuf,
+    backend: BackendKind,
+    counters: Arc<ScanCounters>,
+    started: Instant,
+    phase: ScanPhase,
+    pass: Option<(u32, u32)>,
+    // The walk can run on many threads, so anything it updates besides the
+    // counters sits behind a lock. These are written on rare events: an
+    // emit, an error, a hard link or a followed symlink, and only while
+    // symlinks are followed on every file.
+    last_emit: Mutex<Instant>,
     emit_interval: Duration,
     is_cancelled: Arc<AtomicBool>,
+    children: Arc<ChildProcesses>,
+    deadline: Option<Instant>,
+    budget_deadline: Option<Instant>,
+    // Files seen so far, used to build a partial tree if the scan stops early
+    found_files: HashMap<PathBuf, ByteSizes>,
+    // The tree a walker had built when it stopped, kept whole instead
+    walked_root: Option<FileNode>,
+    skipped: Mutex<Vec<SkippedPath>>,
+    // (st_dev, st_ino) of files already charged: those with several hard
+    // links, or every file once symlinks are followed
+    seen_inodes: Mutex<HashSet<(u64, u64)>>,
+    // Device of the scan root when the scan must stay on one filesystem
+    root_device: Option<u64>,
+    mounts: Vec<Mount>,
+    // Mount points of the filesystems the scan does not enter
+    excluded_mount_points: HashSet<PathBuf>,
+    excluded_mounts: Mutex<Vec<ExcludedMount>>,
+    // (st_dev, st_ino) of directories walked while following symlinks, and
+    // the path each was walked under
+    visited_dirs: Mutex<HashMap<(u64, u64), PathBuf>>,
+    // Canonical scan root, for the within-root symlink policy
+    real_root: Option<PathBuf>,
+    exclusions: Exclusions,
+    // Preview being refined while a hybrid mdfind scan walks the tree
+    backfill: Option<Backfill>,
+    options: ScanOptions,
 }
 
 impl ScanState {
-    fn new() -> Self {
+    fn new(
+        registration: ScanRegistration,
+        path: &Path,
+        backend: BackendKind,
+        options: ScanOptions,
+    ) -> Self {
+        let started = Instant::now();
         Self {
-            items_processed: Arc::new(AtomicU32::new(0)),
-            last_emit: Instant::now(),
+            id: registration.id,
+            path: path.to_path_buf(),
+            backend,
+            counters: registration.counters,
+            started,
+            phase: ScanPhase::Starting,
+            pass: None,
+            last_emit: Mutex::new(Instant::now()),
             emit_interval: Duration::from_millis(100),
-            is_cancelled: Arc::new(AtomicBool::new(false)),
+            is_cancelled: registration.is_cancelled,
+            children: registration.children,
+            deadline: options.timeout_ms.map(|ms| started + Duration::from_millis(ms)),
+            budget_deadline: options.time_budget_ms.map(|ms| started + Duration::from_millis(ms)),
+            found_files: HashMap::new(),
+            walked_root: None,
+            skipped: Mutex::default(),
+            seen_inodes: Mutex::default(),
+            root_device: None,
+            mounts: Vec::new(),
+            excluded_mount_points: HashSet::new(),
+            excluded_mounts: Mutex::default(),
+            visited_dirs: Mutex::default(),
+            real_root: None,
+            exclusions: Exclusions::default(),
+            backfill: None,
+            options,
         }
     }
     
@@ -57,952 +163,302 @@ impl ScanState {
         self.is_cancelled.load(Ordering::Relaxed)
     }
     
-    #[allow(dead_code)]
-    fn cancel(&self) {
-        self.is_cancelled.store(true, Ordering::Relaxed);
+    // A timeout is a hard failure, unlike the time budget
+    fn timed_out(&self) -> bool {
+        self.deadline.is_some_and(|deadline| Instant::now() >= deadline)
     }
     
-    fn should_emit(&mut self) -> bool {
-        self.last_emit.elapsed() >= self.emit_interval
-    }
-    
-    fn emit(&mut self, app_handle: &tauri::AppHandle, path: &str) {
-        let count = self.items_processed.load(Ordering::Relaxed);
-        let progress = ScanProgress {
-            current_path: path.to_string(),
-            items_processed: count,
-        };
-        let _ = app_handle.emit("scan-progress", &progress);
-        self.last_emit = Instant::now();
-    }
-}
-
-// Cancel any ongoing scans
-#[tauri::command]
-pub fn cancel_scan() {
-    if let Some(global_state) = GLOBAL_SCAN_STATE.get() {
-        if let Ok(guard) = global_state.lock() {
-            if let Some(cancel_flag) = guard.as_ref() {
-                cancel_flag.store(true, Ordering::Relaxed);
-                println!("[CANCEL] Scan cancellation requested");
-            }
-        }
-    }
-}
-
-// Add files to cache
-fn add_files_to_cache(files: &HashMap<PathBuf, u64>) {
-    let cache = FILE_CACHE.get_or_init(|| Arc::new(Mutex::new(Vec::new())));
-    if let Ok(mut cache_guard) = cache.lock() {
-        // Add new files to cache
-        for (path, size) in files {
-            cache_guard.push(CachedFile { 
-                path: path.clone(), 
-                size: *size 
-            });
+    // Why the scan should stop early but still return what it has, if at all
+    fn incomplete_reason(&self) -> Option<IncompleteReason> {
+        if self.is_cancelled() {
+            Some(IncompleteReason::Cancelled)
+        } else if self.budget_deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Some(IncompleteReason::TimeBudget)
+        } else {
+            None
         }
-        
-        // Sort by size (largest first) and keep only top 1000
-        cache_guard.sort_by(|a, b| b.size.cmp(&a.size));
-        cache_guard.truncate(1000);
-        
-        println!("[CACHE] Updated cache with {} files", cache_guard.len());
     }
-}
-
-// Get cached files relevant to a directory
-fn get_cached_files_for_directory(target_path: &Path) -> HashMap<PathBuf, u64> {
-    let cache = FILE_CACHE.get_or_init(|| Arc::new(Mutex::new(Vec::new())));
-    let mut relevant_files = HashMap::new();
     
-    if let Ok(cache_guard) = cache.lock() {
-        for cached_file in cache_guard.iter() {
-            if cached_file.path.starts_with(target_path) {
-                relevant_files.insert(cached_file.path.clone(), cached_file.size);
+    // Fails once the scan is cancelled, has used up its time budget or has
+    // run past its timeout
+    fn check_interrupted(&self) -> Result<(), ScanError> {
+        if self.timed_out() {
+            return Err(ScanError::timeout(&self.path, self.options.timeout_ms.unwrap_or_default()));
+        }
+        match self.incomplete_reason() {
+            Some(IncompleteReason::Cancelled) => Err(ScanError::cancelled(&self.path)),
+            Some(IncompleteReason::TimeBudget) => {
+                Err(ScanError::timeout(&self.path, self.options.time_budget_ms.unwrap_or_default()))
             }
+            None => Ok(()),
         }
     }
     
-    if !relevant_files.is_empty() {
-        println!("[CACHE] Found {} cached files for directory: {}", 
-            relevant_files.len(), target_path.display());
+    // Remembers a file for partial results
+    fn record_file(&mut self, path: &Path, sizes: ByteSizes) {
+        self.found_files.insert(path.to_path_buf(), sizes);
     }
     
-    relevant_files
-}
-
-pub fn scan_directory(path: &Path, app_handle: &tauri::AppHandle) -> Result<FileNode, String> {
-    let mut state = ScanState::new();
-    
-    // Register this scan's cancellation flag globally
-    let global_state = GLOBAL_SCAN_STATE.get_or_init(|| Arc::new(Mutex::new(None)));
-    {
-        let mut guard = global_state.lock().unwrap();
-        *guard = Some(state.is_cancelled.clone());
+    fn metric(&self) -> SizeMetric {
+        self.options.size_metric
     }
     
-    // For home directory or very large directories, use smart scanning
-    let path_str = path.to_string_lossy();
-    let home_path = std::env::var("HOME").unwrap_or_default();
-    
-    println!("[SCAN] Starting scan_directory for path: {}, Home: {}", path_str, home_path);
-    
-    // Always use smart scanning (mdfind-based) for all directories
-    let scan_path = if path_str == "~" {
-        Path::new(&home_path)
-    } else {
-        path
-    };
-    
-    println!("[SCAN] Using smart scanning with mdfind for directory: {}", scan_path.display());
-    let result = scan_directory_smart(scan_path, app_handle, &mut state);
-    
-    match &result {
-        Ok(node) => {
-            println!("[SCAN] Scan completed successfully");
-            println!("[SCAN] Result: name={}, path={}, size={}, is_dir={}, children_count={}", 
-                node.name, node.path, node.size, node.is_dir, 
-                node.children.as_ref().map(|c| c.len()).unwrap_or(0));
-        },
-        Err(e) => {
-            println!("[SCAN] Scan failed with error: {}", e);
+    // Whether the symlink policy lets the scan descend through `link`
+    fn follows(&self, link: &Path) -> bool {
+        match self.options.symlink_policy {
+            SymlinkPolicy::Never => false,
+            SymlinkPolicy::Always => true,
+            SymlinkPolicy::WithinRoot => fs::canonicalize(link)
+                .is_ok_and(|target| self.real_root.as_ref().is_some_and(|root| target.starts_with(root))),
         }
     }
     
-    result
-}
-
-// Scans directory to a specific depth, calculating sizes for all subdirectories
-#[allow(dead_code)]
-fn scan_directory_shallow(
-    path: &Path,
-    app_handle: &tauri::AppHandle,
-    state: &mut ScanState,
-    current_depth: u32,
-    target_depth: u32,
-) -> Result<FileNode, String> {
-    let metadata = fs::metadata(path)
-        .map_err(|e| format!("Failed to read metadata: {}", e))?;
-    
-    let name = path.file_name()
-        .unwrap_or_default()
-        .to_string_lossy()
-        .to_string();
-    
-    state.items_processed.fetch_add(1, Ordering::Relaxed);
-    
-    if state.should_emit() {
-        state.emit(app_handle, &path.to_string_lossy());
-    }
-    
-    if metadata.is_file() {
-        return Ok(FileNode {
-            name,
-            path: path.to_string_lossy().to_string(),
-            size: metadata.len(),
-            is_dir: false,
-            children: None,
-        });
-    }
-    
-    // For directories
-    let mut children = Vec::new();
-    let mut total_size = 0u64;
-    
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries.flatten() {
-            let entry_path = entry.path();
-            
-            if current_depth < target_depth {
-                // Scan subdirectories up to target depth
-                if let Ok(child) = scan_directory_shallow(&entry_path, app_handle, state, current_depth + 1, target_depth) {
-                    total_size += child.size;
-                    children.push(child);
-                }
-            } else {
-                // Just calculate size for directories beyond target depth
-                if let Ok(size) = calculate_directory_size(&entry_path, state) {
-                    total_size += size;
-                    
-                    // Create a node with aggregated size but no children
-                    let child_name = entry_path.file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_string();
-                    
-                    if let Ok(meta) = entry.metadata() {
-                        children.push(FileNode {
-                            name: child_name,
-                            path: entry_path.to_string_lossy().to_string(),
-                            size,
-                            is_dir: meta.is_dir(),
-                            children: None, // Don't include children at this depth
-                        });
-                    }
-                }
-            }
+    // Applies the exclusion rules to a tree an external tool produced, which
+    // could not skip anything itself. Returns the bytes removed along with
+    // skipped subtrees.
+    fn prune_excluded(&self, node: &mut FileNode) -> ByteSizes {
+        let mut removed = ByteSizes::default();
+        if self.exclusions.is_empty() {
+            return removed;
         }
-    }
-    
-    Ok(FileNode {
-        name,
-        path: path.to_string_lossy().to_string(),
-        size: total_size,
-        is_dir: true,
-        children: if children.is_empty() { None } else { Some(children) },
-    })
-}
-
-// Fast recursive size calculation without building full tree structure
-#[allow(dead_code)]
-fn calculate_directory_size(path: &Path, state: &mut ScanState) -> Result<u64, String> {
-    let mut total_size = 0u64;
-    
-    if let Ok(entries) = fs::read_dir(path) {
-        for entry in entries.flatten() {
-            let entry_path = entry.path();
-            state.items_processed.fetch_add(1, Ordering::Relaxed);
-            
-            if let Ok(metadata) = entry.metadata() {
-                if metadata.is_file() {
-                    total_size += metadata.len();
-                } else if metadata.is_dir() {
-                    // Recursively calculate subdirectory sizes
-                    if let Ok(dir_size) = calculate_directory_size(&entry_path, state) {
-                        total_size += dir_size;
-                    }
-                }
+        let Some(children) = node.children.as_mut() else { return removed };
+        children.retain_mut(|child| {
+            // Aggregates and a directory's own blocks have no path to match
+            if child.path.is_empty() {
+                return true;
             }
-        }
-    }
-    
-    Ok(total_size)
-}
-
-// Fast parallel directory size calculation
-#[allow(dead_code)]
-fn calculate_directory_size_fast(path: &Path) -> Result<u64, String> {
-    use rayon::prelude::*;
-    use std::sync::atomic::AtomicU64;
-    use std::sync::Mutex;
-    
-    let total_size = Arc::new(AtomicU64::new(0));
-    
-    // Use a work-stealing queue for directories to process
-    let dirs_to_process = Arc::new(Mutex::new(vec![path.to_path_buf()]));
-    
-    while let Some(dir) = {
-        let mut dirs = dirs_to_process.lock().unwrap();
-        dirs.pop()
-    } {
-        if let Ok(entries) = fs::read_dir(&dir) {
-            let entries: Vec<_> = entries.filter_map(|e| e.ok()).collect();
-            
-            entries.par_iter().for_each(|entry| {
-                if let Ok(metadata) = entry.metadata() {
-                    if metadata.is_file() {
-                        total_size.fetch_add(metadata.len(), Ordering::Relaxed);
-                    } else if metadata.is_dir() {
-                        // Add subdirectory to process queue
-                        if let Ok(mut dirs) = dirs_to_process.lock() {
-                            dirs.push(entry.path());
-                        }
-                    }
+            match self.exclusions.check(&child.os_path(), child.is_dir) {
+                Some(ExcludeMode::Skip) => {
+                    removed += child.sizes();
+                    false
                 }
-            });
-        }
-    }
-    
-    Ok(total_size.load(Ordering::Relaxed))
-}
-
-// Smart scanning using mdfind for blazing fast initial results
-fn scan_directory_smart(
-    path: &Path,
-    app_handle: &tauri::AppHandle,
-    state: &mut ScanState,
-) -> Result<FileNode, String> {
-    // Always use mdfind on macOS for now
-    if cfg!(target_os = "macos") {
-        return scan_directory_with_mdfind(path, app_handle, state);
-    }
-    
-    // Fallback to du for non-macOS systems
-    use std::process::{Command, Stdio};
-    
-    let path_str = path.to_string_lossy();
-    
-    println!("[SMART] Smart scanning directory: {}", path.display());
-    
-    // For now, use system dust command
-    let dust_path = "dust".to_string();
-    println!("[SMART] Using system dust command");
-    
-    // For now, skip dust and use du directly for large directories
-    // Dust can be too slow on very large directories with many files
-    let use_dust = false;
-    
-    if use_dust {
-        println!("[SMART] Using dust for fast scanning");
-        
-        // Run dust with JSON output for easy parsing
-        // Use -s flag to suppress progress output which interferes with JSON parsing
-        let mut cmd = Command::new(&dust_path)
-            .arg("-d")
-            .arg("1") // Depth 1
-            .arg("-n")
-            .arg("200") // Max 200 items
-            .arg("-j") // JSON output
-            .arg("-s") // Suppress progress output for clean JSON
-            .arg(path)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null()) // Discard stderr to avoid progress output
-            .spawn()
-            .map_err(|e| format!("Failed to start dust: {}", e))?;
-        
-        let stdout = cmd.stdout.take()
-            .ok_or_else(|| "Failed to capture stdout".to_string())?;
-        
-        // Read line by line to get just the JSON line and ignore progress
-        use std::io::{BufRead, BufReader};
-        let reader = BufReader::new(stdout);
-        let mut json_line = String::new();
-        
-        // The first line should be the JSON output
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                if line.trim().starts_with('{') {
-                    json_line = line;
-                    break;
+                Some(ExcludeMode::Collapse) => {
+                    child.collapse();
+                    true
                 }
-            }
-        }
-        
-        println!("[SMART] Dust JSON output length: {} chars", json_line.len());
-        if json_line.is_empty() {
-            println!("[SMART] ERROR: No JSON output received from dust!");
-        } else if json_line.len() < 1000 {
-            println!("[SMART] Dust JSON output: {}", json_line);
-        } else {
-            println!("[SMART] Dust JSON output (first 1000 chars): {}", &json_line[..1000]);
-        }
-        
-        // Don't wait yet - read all output first
-        println!("[SMART] Waiting for dust to complete...");
-        
-        // Now wait for the process to complete
-        let status = cmd.wait()
-            .map_err(|e| format!("Failed to wait for dust: {}", e))?;
-        println!("[SMART] Dust exit status: {:?}", status);
-        
-        // Parse dust JSON output
-        if let Ok(json_data) = serde_json::from_str::<serde_json::Value>(&json_line) {
-            println!("[SMART] Parsed JSON successfully");
-            // Dust returns a single object with children array
-            if let Some(children_array) = json_data.get("children").and_then(|c| c.as_array()) {
-                println!("[SMART] Found {} children in dust output", children_array.len());
-                let mut children = Vec::new();
-                let mut total_size = 0u64;
-                
-                for (idx, item) in children_array.iter().enumerate() {
-                    if idx < 5 {
-                        println!("[SMART] Processing child {}: {:?}", idx, item);
-                    }
-                    if let (Some(name), Some(size_str)) = (
-                        item.get("name").and_then(|n| n.as_str()),
-                        item.get("size").and_then(|s| s.as_str())
-                    ) {
-                        // Remove ./ prefix if present
-                        let name = name.strip_prefix("./").unwrap_or(name);
-                        
-                        // Parse size from dust's human-readable format
-                        let size = parse_human_size(size_str);
-                        let full_path = path.join(name);
-                        let is_dir = full_path.is_dir();
-                        
-                        // Update progress
-                        state.items_processed.fetch_add(1, Ordering::Relaxed);
-                        if idx % 10 == 0 || state.should_emit() {
-                            state.emit(app_handle, &format!("{} ({} items)", name, idx + 1));
-                        }
-                        
-                        children.push(FileNode {
-                            name: name.to_string(),
-                            path: full_path.to_string_lossy().to_string(),
-                            size,
-                            is_dir,
-                            children: None,
-                        });
-                        
-                        total_size += size;
-                    }
+                None => {
+                    removed += self.prune_excluded(child);
+                    true
                 }
-                
-                // Sort by size
-                children.sort_by(|a, b| b.size.cmp(&a.size));
-                
-                // Filter small files if we have many
-                if children.len() > 50 {
-                    let min_size = 1024 * 1024; // 1MB
-                    children.retain(|child| child.size >= min_size || child.is_dir);
-                    children.truncate(100);
-                }
-                
-                println!("[SMART] Dust scan complete: found {} significant items, total size: {}", children.len(), format_size(total_size));
-                
-                // Get proper name for the directory
-                let name = if path_str == "/" {
-                    "Root".to_string()
-                } else if path == Path::new(&std::env::var("HOME").unwrap_or_default()) {
-                    "Home".to_string()
-                } else {
-                    path.file_name()
-                        .map(|n| n.to_string_lossy().to_string())
-                        .unwrap_or_else(|| path_str.to_string())
-                };
-                
-                return Ok(FileNode {
-                    name,
-                    path: path_str.to_string(),
-                    size: total_size,
-                    is_dir: true,
-                    children: if children.is_empty() { None } else { Some(children) },
-                });
-            } else {
-                println!("[SMART] ERROR: No children array found in dust output");
-                println!("[SMART] JSON structure: {:?}", json_data);
             }
-        } else {
-            println!("[SMART] ERROR: Failed to parse dust JSON output");
-            println!("[SMART] Raw JSON line was: {}", json_line);
+        });
+        if children.is_empty() {
+            node.children = None;
         }
-    }
-    
-    // Use du command for fast initial scanning
-    println!("[SMART] Using du command for fast directory scanning");
-    state.emit(app_handle, "Starting detailed du scan for comprehensive results...");
-    scan_directory_with_du(path, app_handle, state)
-}
-
-// Multi-pass mdfind scanning for macOS
-fn scan_directory_with_mdfind(
-    path: &Path,
-    app_handle: &tauri::AppHandle,
-    state: &mut ScanState,
-) -> Result<FileNode, String> {
-    use std::process::Command;
-    
-    let path_str = path.to_string_lossy();
-    println!("[MDFIND] Starting multi-pass mdfind scan on: {}", path_str);
-    
-    // Multiple passes with decreasing size thresholds
-    // Pass 1: > 100MB, Pass 2: > 50MB, Pass 3: > 10MB, Pass 4: > 5MB
-    let size_thresholds = [
-        (104857600u64, "100MB"),  // 100MB
-        (52428800u64, "50MB"),    // 50MB  
-        (10485760u64, "10MB"),    // 10MB
-        (5242880u64, "5MB"),      // 5MB
-    ];
-    
-    let mut all_files: HashMap<PathBuf, u64> = HashMap::new();
-    let mut directory_sizes: HashMap<PathBuf, u64> = HashMap::new();
-    
-    // Get cached files for this directory to emit as intermediate results
-    let cached_files = get_cached_files_for_directory(path);
-    if !cached_files.is_empty() {
-        println!("[MDFIND] Found {} cached files - emitting as initial preview", cached_files.len());
-        
-        // Build temporary tree from cached files for immediate display
-        let mut cached_tree_files = HashMap::new();
-        let mut cached_tree_dirs = HashMap::new();
-        
-        for (file_path, size) in &cached_files {
-            cached_tree_files.insert(file_path.clone(), *size);
-            
-            // Update directory sizes for cached results
-            let mut current_dir = file_path.parent();
-            while let Some(dir) = current_dir {
-                if dir.starts_with(path) || dir == path {
-                    *cached_tree_dirs.entry(dir.to_path_buf()).or_insert(0) += size;
-                }
-                current_dir = dir.parent();
-            }
+        let mut sizes = node.sizes();
+        sizes -= removed;
+        node.set_sizes(sizes, self.metric());
+        removed
+    }
+    
+    // A tool that reports only some levels counts what skip rules leave out
+    // below them in the totals above, so it cannot take those rules, nor
+    // hide dot-entries unless it `hides_dot_entries` itself. Collapse rules
+    // only drop children and are applied to its tree afterwards.
+    fn refuse_skip_rules(&self, path: &Path, hides_dot_entries: bool) -> Result<(), ScanError> {
+        if self.exclusions.skips_entries() {
+            return Err(ScanError::unsupported_by(path, self.backend, "Skipping excluded entries"));
         }
-        
-        // Emit cached results as intermediate preview only - don't seed the actual scan
-        if let Ok(cached_tree) = build_tree_from_files(&cached_tree_files, &cached_tree_dirs, path) {
-            match app_handle.emit("scan-intermediate", &cached_tree) {
-                Ok(_) => println!("[MDFIND] Emitted cached preview ({} files) - starting fresh mdfind scan", cached_files.len()),
-                Err(e) => println!("[MDFIND] Failed to emit cached preview: {:?}", e),
-            }
+        if self.options.hidden == HiddenEntries::Hide && !hides_dot_entries {
+            return Err(ScanError::unsupported_by(path, self.backend, "Hiding dot-entries"));
         }
+        Ok(())
     }
     
-    // Always start fresh mdfind scan regardless of cache
-    
-    for (pass_idx, (threshold, threshold_name)) in size_thresholds.iter().enumerate() {
-        // Check for cancellation
-        if state.is_cancelled() {
-            println!("[MDFIND] Scan cancelled during pass {}", pass_idx + 1);
-            return Err("Scan cancelled".to_string());
-        }
-        
-        state.emit(app_handle, &format!("mdfind pass {}/{}: Finding files larger than {}", 
-            pass_idx + 1, size_thresholds.len(), threshold_name));
-        
-        // Run mdfind command
-        let mdfind_cmd = format!(
-            "mdfind -onlyin '{}' 'kMDItemFSSize > {}' | head -2000 | while IFS= read -r file; do stat -f '%z %N' \"$file\" 2>/dev/null; done | sort -nr | head -1000",
-            path_str, threshold
-        );
-        
-        let output = Command::new("sh")
-            .arg("-c")
-            .arg(&mdfind_cmd)
-            .output()
-            .map_err(|e| format!("Failed to execute mdfind: {}", e))?;
-        
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let new_files_count = stdout.lines().count();
-        let files_before_pass = all_files.len();
-        println!("[MDFIND] Pass {} found {} files from mdfind", pass_idx + 1, new_files_count);
-        
-        // Parse the output
-        for line in stdout.lines() {
-            if let Some(space_idx) = line.find(' ') {
-                let (size_str, file_path) = line.split_at(space_idx);
-                let file_path = file_path.trim();
-                
-                if let Ok(size) = size_str.parse::<u64>() {
-                    let file_path_buf = PathBuf::from(file_path);
-                    
-                    // Only process files within our target directory
-                    if file_path_buf.starts_with(path) {
-                        // Skip if we already have this file from a previous pass or cache
-                        if !all_files.contains_key(&file_path_buf) {
-                            all_files.insert(file_path_buf.clone(), size);
-                            
-                            // Update directory sizes for all parent directories
-                            let mut current_dir = file_path_buf.parent();
-                            while let Some(dir) = current_dir {
-                                if dir.starts_with(path) || dir == path {
-                                    *directory_sizes.entry(dir.to_path_buf()).or_insert(0) += size;
-                                }
-                                current_dir = dir.parent();
-                            }
-                        } else {
-                            // File was already known (from cache or previous pass)
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Update progress
-        let files_after_pass = all_files.len();
-        let new_files_added = files_after_pass - files_before_pass;
-        state.items_processed.store(all_files.len() as u32, Ordering::Relaxed);
-        state.emit(app_handle, &format!("mdfind: Found {} large files ({} new in this pass {}/{})", 
-            all_files.len(), new_files_added, pass_idx + 1, size_thresholds.len()));
-        
-        println!("[MDFIND] Pass {} added {} new files (total: {})", 
-            pass_idx + 1, new_files_added, files_after_pass);
-        
-        // Emit intermediate results after each pass if we have files
-        if all_files.len() > 10 {
-            println!("[MDFIND] Emitting intermediate results with {} files", all_files.len());
-            // Build and emit intermediate tree
-            if let Ok(intermediate_tree) = build_tree_from_files(&all_files, &directory_sizes, path) {
-                // Emit intermediate result event
-                match app_handle.emit("scan-intermediate", &intermediate_tree) {
-                    Ok(_) => println!("[MDFIND] Successfully emitted intermediate results"),
-                    Err(e) => println!("[MDFIND] Failed to emit intermediate results: {:?}", e),
-                }
-            }
-        }
+    // Records a path that could not be read and returns a node standing in
+    // for it in the tree
+    fn skip(&self, path: &Path, is_dir: bool, error: &std::io::Error) -> FileNode {
+        self.record_skipped(SkippedPath::from_io(path, error));
+        FileNode::inaccessible(path, is_dir)
     }
     
-    // Emit final status update
-    state.emit(app_handle, &format!("mdfind complete: {} files found. Building directory tree...", all_files.len()));
-    
-    // Update cache with discovered files
-    add_files_to_cache(&all_files);
-    
-    // Build final tree structure from collected files
-    build_tree_from_files(&all_files, &directory_sizes, path)
-}
-
-// Helper function to recursively build subtree
-fn build_subtree_recursive(
-    dir_path: &Path,
-    dir_nodes: &mut HashMap<PathBuf, FileNode>,
-    dir_contents: &mut HashMap<PathBuf, Vec<FileNode>>,
-    all_dirs: &[PathBuf],
-    current_level: usize,
-) -> Option<FileNode> {
-    let dir_name = dir_path.file_name()
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_default();
-    
-    // Start with the basic directory node
-    let mut dir_node = if let Some(existing) = dir_nodes.remove(dir_path) {
-        existing
-    } else {
-        FileNode {
-            name: dir_name,
-            path: dir_path.to_string_lossy().to_string(),
-            size: 0,
-            is_dir: true,
-            children: None,
-        }
-    };
-    
-    let mut children = Vec::new();
-    
-    // Add files directly in this directory
-    if let Some(files) = dir_contents.remove(dir_path) {
-        children.extend(files);
+    fn record_skipped(&self, skipped: SkippedPath) {
+        println!("[SCAN] Skipping {} ({:?})", skipped.path, skipped.reason);
+        self.counters.add_error();
+        lock(&self.skipped).push(skipped);
     }
     
-    // Find and add subdirectories (only go 2 levels deep from root)
-    if current_level <= 2 {
-        let subdirs: Vec<PathBuf> = all_dirs.iter()
-            .filter(|p| {
-                p.parent() == Some(dir_path) && p != &dir_path
-            })
-            .cloned()
-            .collect();
-        
-        for subdir in subdirs {
-            if let Some(subdir_tree) = build_subtree_recursive(&subdir, dir_nodes, dir_contents, all_dirs, current_level + 1) {
-                children.push(subdir_tree);
-            }
+    // Builds the best tree possible from what was seen before stopping
+    fn partial_tree(&mut self, root_path: &Path) -> FileNode {
+        match self.walked_root.take() {
+            Some(root) => root,
+            None => tree::build_tree(&self.found_files, root_path, self.metric()),
         }
     }
     
-    // Sort children by size
-    children.sort_by(|a, b| b.size.cmp(&a.size));
-    
-    // Limit children to top 50 items
-    if children.len() > 50 {
-        children.truncate(50);
+    // Another thread holding the lock is about to emit anyway
+    fn should_emit(&self) -> bool {
+        self.last_emit.try_lock().is_ok_and(|last_emit| last_emit.elapsed() >= self.emit_interval)
     }
     
-    if !children.is_empty() {
-        dir_node.children = Some(children);
+    fn set_phase(&mut self, phase: ScanPhase) {
+        self.phase = phase;
     }
     
-    Some(dir_node)
-}
-
-// Helper function to build tree structure from file list
-fn build_tree_from_files(
-    files: &HashMap<PathBuf, u64>,
-    dir_sizes: &HashMap<PathBuf, u64>,
-    root_path: &Path,
-) -> Result<FileNode, String> {
-    
-    // Create a hierarchical structure
-    let root_path_str = root_path.to_string_lossy();
-    let home_path = std::env::var("HOME").unwrap_or_default();
-    
-    let root_name = if root_path_str == "/" {
-        "Root".to_string()
-    } else if root_path_str == home_path {
-        "Home".to_string()
-    } else {
-        root_path.file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| "Root".to_string())
-    };
-    
-    // Calculate total size from all files
-    let total_size: u64 = files.values().sum();
-    
-    let mut root = FileNode {
-        name: root_name,
-        path: root_path_str.to_string(),
-        size: total_size,
-        is_dir: true,
-        children: Some(Vec::new()),
-    };
-    
-    // Group files by their parent directory
-    let mut dir_contents: HashMap<PathBuf, Vec<FileNode>> = HashMap::new();
-    
-    for (file_path, size) in files {
-        if let Some(parent) = file_path.parent() {
-            let file_node = FileNode {
-                name: file_path.file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_default(),
-                path: file_path.to_string_lossy().to_string(),
-                size: *size,
-                is_dir: false,
-                children: None,
-            };
-            
-            dir_contents.entry(parent.to_path_buf())
-                .or_insert_with(Vec::new)
-                .push(file_node);
-        }
+    // Records the current pass of a multi-pass backend (1-based)
+    fn set_pass(&mut self, pass: u32, total_passes: u32) {
+        self.pass = Some((pass, total_passes));
     }
     
-    // Add directories that contain files
-    let mut all_dirs: Vec<PathBuf> = dir_sizes.keys().cloned().collect();
-    all_dirs.sort_by(|a, b| a.components().count().cmp(&b.components().count()));
-    
-    // Build directory nodes - but don't process them yet, we'll do it recursively
-    let mut dir_nodes: HashMap<PathBuf, FileNode> = HashMap::new();
-    
-    // Get first-level directories under root
-    let mut root_children: Vec<FileNode> = Vec::new();
-    
-    // Find all immediate subdirectories of root
-    let root_level = root_path.components().count();
-    let mut immediate_subdirs: Vec<PathBuf> = all_dirs.iter()
-        .filter(|p| p.components().count() == root_level + 1 && p.starts_with(root_path))
-        .cloned()
-        .collect();
-    immediate_subdirs.sort();
-    
-    println!("[BUILD] Found {} immediate subdirectories", immediate_subdirs.len());
-    
-    // Build tree for each immediate subdirectory
-    for subdir in immediate_subdirs {
-        let subdir_size = *dir_sizes.get(&subdir).unwrap_or(&0);
-        if subdir_size > 0 {
-            // Build complete subtree for this directory
-            if let Some(subdir_tree) = build_subtree_recursive(&subdir, &mut dir_nodes, &mut dir_contents, &all_dirs, root_level + 1) {
-                root_children.push(subdir_tree);
-            }
-        }
+    fn emit(&self, app_handle: &tauri::AppHandle, current_directory: &str) {
+        let progress = ScanProgress {
+            scan_id: self.id.clone(),
+            phase: self.phase,
+            backend: self.backend,
+            totals: self.counters.snapshot(),
+            current_directory: current_directory.to_string(),
+            elapsed_ms: self.started.elapsed().as_millis() as u64,
+            pass: self.pass.map(|(pass, _)| pass),
+            total_passes: self.pass.map(|(_, total)| total),
+        };
+        let _ = app_handle.emit("scan-progress", &progress);
+        *lock(&self.last_emit) = Instant::now();
     }
     
-    // Add any files directly in root
-    if let Some(root_files) = dir_contents.remove(root_path) {
-        for file in root_files {
-            root_children.push(file);
+    // Hands a directory the native walk has finished to a running backfill,
+    // sending out the refined preview now and then
+    fn backfill_directory(&self, app_handle: &tauri::AppHandle, dir: &Path, sizes: ByteSizes) {
+        let Some(backfill) = self.backfill.as_ref() else { return };
+        if backfill.record(dir, sizes) && backfill.should_emit() {
+            if let Err(e) = self.emit_intermediate(app_handle, backfill.tree()) {
+                println!("[BACKFILL] Failed to emit refined preview: {:?}", e);
+            }
         }
     }
     
-    // Sort children by size
-    root_children.sort_by(|a, b| b.size.cmp(&a.size));
-    
-    // Only show the largest items in the treemap
-    if root_children.len() > 100 {
-        root_children.truncate(100);
+    // Publishes a partial tree tagged with this scan's ID
+    fn emit_intermediate(
+        &self,
+        app_handle: &tauri::AppHandle,
+        root: FileNode,
+    ) -> Result<(), tauri::Error> {
+        app_handle.emit("scan-intermediate", ScanIntermediate { scan_id: self.id.clone(), root })
     }
-    
-    root.children = if root_children.is_empty() { None } else { Some(root_children) };
-    
-    println!("[BUILD] Final tree has {} children, total size: {}", 
-        root.children.as_ref().map(|c| c.len()).unwrap_or(0),
-        format_size(root.size));
-    
-    Ok(root)
 }
 
-// Fallback scanning using du command
-fn scan_directory_with_du(
+pub fn scan_directory(
     path: &Path,
+    options: &ScanOptions,
+    scan_id: Option<String>,
+    manager: &ScanManager,
     app_handle: &tauri::AppHandle,
-    state: &mut ScanState,
-) -> Result<FileNode, String> {
-    use std::process::Command;
-    
-    let path_str = path.to_string_lossy();
-    
-    // Use a simpler approach - run du and collect all output at once
-    println!("[DU] Running du command on: {}", path.display());
-    
-    // First, get a list of visible files and directories
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg(&format!("cd '{}' && du -sk * 2>/dev/null | sort -rn | head -100", path.display()))
-        .output()
-        .map_err(|e| format!("Failed to execute du: {}", e))?;
-    
-    if !output.status.success() {
-        println!("[DU] du command failed with status: {:?}", output.status);
-    }
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    println!("[DU] du output length: {} chars", stdout.len());
-    
-    let mut children = Vec::new();
-    let mut total_size = 0u64;
-    
-    // Check cache for any relevant files in this directory
-    let cached_files = get_cached_files_for_directory(path);
-    for (cached_path, cached_size) in cached_files {
-        if let Some(name) = cached_path.file_name() {
-            children.push(FileNode {
-                name: name.to_string_lossy().to_string(),
-                path: cached_path.to_string_lossy().to_string(),
-                size: cached_size,
-                is_dir: cached_path.is_dir(),
-                children: None,
-            });
-            total_size += cached_size;
+) -> Result<ScanResult, ScanError> {
+    println!("[SCAN] Starting scan_directory for path: {}", path.display());
+    
+    let root = paths::resolve_root(path)?;
+    let scan_path = root.path.as_path();
+    
+    let kind = options.backend.unwrap_or_else(|| default_backend(app_handle));
+    let backend = backend_for(kind);
+    if !backend.is_available(app_handle) {
+        return Err(ScanError::backend_unavailable(scan_path, kind));
+    }
+    
+    // Report a missing or unreadable root the same way for every backend
+    let root_metadata = fs::metadata(scan_path).map_err(|e| ScanError::io(scan_path, &e))?;
+    let exclusions = Exclusions::new(scan_path, options)?;
+    
+    // Register with the manager so this scan can be cancelled on its own
+    let registration = manager.begin(scan_id, scan_path, kind)?;
+    let mut state = ScanState::new(registration, scan_path, kind, options.clone());
+    state.set_mounts(mounts::read_mounts());
+    state.exclusions = exclusions;
+    if options.one_filesystem.unwrap_or(scan_path == Path::new("/")) {
+        state.root_device = mounts::device_id(&root_metadata);
+    }
+    if options.symlink_policy == SymlinkPolicy::WithinRoot {
+        state.real_root = Some(scan_path.to_path_buf());
+    }
+    state.emit(app_handle, &scan_path.to_string_lossy());
+    state.set_phase(ScanPhase::Scanning);
+    
+    println!("[SCAN] Using {} backend for directory: {}", backend.name(), scan_path.display());
+    let mut result = backend.scan(scan_path, app_handle, &mut state);
+    
+    // An automatically chosen native walk falls back to du if it cannot read
+    // the root
+    let du = backend_for(BackendKind::Du);
+    if options.backend.is_none()
+        && kind == BackendKind::Native
+        && state.check_interrupted().is_ok()
+        && du.is_available(app_handle)
+    {
+        if let Err(e) = &result {
+            println!("[SCAN] Native walker failed ({}), falling back to du", e);
+            result = du.scan(scan_path, app_handle, &mut state);
         }
     }
     
-    // Parse du output
-    let lines: Vec<&str> = stdout.lines().collect();
-    println!("[DU] Got {} lines from du", lines.len());
-    
-    for (idx, line) in lines.iter().enumerate() {
-        // Check for cancellation
-        if state.is_cancelled() {
-            println!("[DU] Scan cancelled during processing");
-            return Err("Scan cancelled".to_string());
-        }
-        
-        if line.trim().is_empty() {
-            continue;
-        }
-        
-        // Parse line like "294912  node_modules" (size in KB)
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 2 {
-            let size_kb_str = parts[0].trim();
-            let name = parts[1..].join(" "); // Handle names with spaces
-            
-            // Parse size (du -sk gives size in KB)
-            if let Ok(size_kb) = size_kb_str.parse::<u64>() {
-                let size = size_kb * 1024; // Convert to bytes
-                let full_path = path.join(&name);
-                let is_dir = full_path.is_dir();
-                
-                // Update progress
-                if idx % 5 == 0 {
-                    state.items_processed.fetch_add(1, Ordering::Relaxed);
-                    state.emit(app_handle, &format!("du scan: Found {} items", idx + 1));
-                }
-                
-                children.push(FileNode {
-                    name: name.clone(),
-                    path: full_path.to_string_lossy().to_string(),
-                    size,
-                    is_dir,
-                    children: None,
-                });
-                
-                total_size += size;
-                
-                if idx < 5 {
-                    println!("[DU] Item {}: {} ({} KB)", idx, name, size_kb);
-                }
+    // A cancelled or out-of-budget scan returns whatever it found so far
+    let mut result = match result {
+        Ok(root) => Ok(ScanResult {
+            root,
+            complete: true,
+            incomplete_reason: None,
+            skipped: std::mem::take(&mut *lock(&state.skipped)),
+            excluded_mounts: std::mem::take(&mut *lock(&state.excluded_mounts)),
+        }),
+        Err(e) => match state.incomplete_reason().filter(|_| !state.timed_out()) {
+            Some(reason) => {
+                println!("[SCAN] Scan stopped early ({}), building partial tree", e);
+                state.set_phase(ScanPhase::BuildingTree);
+                state.emit(app_handle, &scan_path.to_string_lossy());
+                Ok(ScanResult {
+                    root: state.partial_tree(scan_path),
+                    complete: false,
+                    incomplete_reason: Some(reason),
+                    skipped: std::mem::take(&mut *lock(&state.skipped)),
+                    excluded_mounts: std::mem::take(&mut *lock(&state.excluded_mounts)),
+                })
             }
-        }
-    }
-    
-    println!("[DU] Found {} children before filtering", children.len());
+            None => Err(e),
+        },
+    };
     
-    // If we got no results from du, try a different approach
-    if children.is_empty() {
-        println!("[DU] WARNING: No results from du command, trying ls approach");
-        // Try listing directory contents directly
-        if let Ok(entries) = fs::read_dir(path) {
-            for (idx, entry) in entries.enumerate() {
-                if let Ok(entry) = entry {
-                    if let Ok(metadata) = entry.metadata() {
-                        let name = entry.file_name().to_string_lossy().to_string();
-                        // Skip hidden files starting with .
-                        if !name.starts_with('.') {
-                            let size = if metadata.is_dir() {
-                                // For directories, estimate size (we'll scan them later)
-                                1024 * 1024 // 1MB placeholder
-                            } else {
-                                metadata.len()
-                            };
-                            
-                            children.push(FileNode {
-                                name: name.clone(),
-                                path: entry.path().to_string_lossy().to_string(),
-                                size,
-                                is_dir: metadata.is_dir(),
-                                children: None,
-                            });
-                            
-                            total_size += size;
-                            
-                            // Update progress
-                            state.items_processed.fetch_add(1, Ordering::Relaxed);
-                            if idx % 5 == 0 || state.should_emit() {
-                                state.emit(app_handle, &format!("du fallback: Found {} items", children.len()));
-                            }
-                        }
-                    }
-                }
-            }
+    if let Ok(scan_result) = result.as_mut() {
+        scan_result.root.apply_limits(&options.tree_limits());
+        // Named as the user spelled the root rather than where symlinks led
+        scan_result.root.name = root_display_name(&root.display);
+        if options.hidden == HiddenEntries::Dim {
+            scan_result.root.mark_hidden();
         }
     }
     
-    // Sort by size (largest first)
-    children.sort_by(|a, b| b.size.cmp(&a.size));
-    
-    // Filter out very small files (less than 1MB) if we have many items
-    if children.len() > 50 {
-        let min_size = 1024 * 1024; // 1MB threshold
-        children.retain(|child| child.size >= min_size || child.is_dir);
-        children.truncate(100); // Keep only top 100 items
+    match &result {
+        Ok(ScanResult { root: node, complete, .. }) => {
+            println!("[SCAN] Scan completed successfully (complete: {})", complete);
+            println!("[SCAN] Result: name={}, path={}, size={}, is_dir={}, children_count={}", 
+                node.name, node.path, node.size, node.is_dir, 
+                node.children.as_ref().map(|c| c.len()).unwrap_or(0));
+        },
+        Err(e) => {
+            println!("[SCAN] Scan failed with error: {}", e);
+        }
     }
     
-    println!("[DU] Scan complete: found {} significant items, total size: {}", children.len(), format_size(total_size));
+    state.set_phase(ScanPhase::Finished);
+    state.emit(app_handle, &scan_path.to_string_lossy());
     
-    // Get proper name for the directory
-    let name = if path_str == "/" {
+    manager.finish(&state.id, &result);
+    result
+}
+
+// Display name for a scan root ("Root", "Home" or the final component)
+fn root_display_name(path: &Path) -> String {
+    if path == Path::new("/") {
         "Root".to_string()
-    } else if path == Path::new(&std::env::var("HOME").unwrap_or_default()) {
+    } else if dirs::home_dir().is_some_and(|home| path == home) {
         "Home".to_string()
     } else {
         path.file_name()
             .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| path_str.to_string())
-    };
-
-    Ok(FileNode {
-        name,
-        path: path_str.to_string(),
-        size: total_size,
-        is_dir: true,
-        children: if children.is_empty() { None } else { Some(children) },
-    })
+            .unwrap_or_else(|| path.to_string_lossy().to_string())
+    }
 }
 
-// Helper function to parse human-readable sizes like "4.0K", "294M", "3.5G"
-fn parse_human_size(size_str: &str) -> u64 {
-    if size_str.trim().is_empty() || size_str == "0B" {
-        return 0;
-    }
-    
-    // Remove any whitespace
-    let size_str = size_str.trim();
-    
-    // Find where the number ends and unit begins
-    let num_end = size_str.find(|c: char| !c.is_numeric() && c != '.').unwrap_or(size_str.len());
-    
-    if num_end == 0 {
-        return 0;
-    }
-    
-    let (num_str, unit) = size_str.split_at(num_end);
-    let number: f64 = num_str.parse().unwrap_or(0.0);
-    
-    // Parse unit (K, M, G, T, P)
-    let multiplier = match unit.trim().to_uppercase().as_str() {
-        "B" | "" => 1.0,
-        "K" | "KB" => 1024.0,
-        "M" | "MB" => 1024.0 * 1024.0,
-        "G" | "GB" => 1024.0 * 1024.0 * 1024.0,
-        "T" | "TB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
-        "P" | "PB" => 1024.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0,
-        _ => 1.0,
-    };
-    
-    (number * multiplier) as u64
+// Where a symlink points, or None for anything that is not a symlink
+fn symlink_target(path: &Path) -> Option<String> {
+    fs::read_link(path).ok().map(|target| target.to_string_lossy().to_string())
 }
 
 // Helper function to format sizes
@@ -1018,9 +474,3 @@ fn format_size(size: u64) -> String {
     
     format!("{:.1}{}", size, UNITS[unit_index])
 }
-
-
-
-
-
-
@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    du::DuBackend, dust::DustBackend, error::ScanError, mdfind::MdfindBackend,
+    native::NativeBackend, FileNode, ScanState,
+};
+
+// Identifies a scan backend across the IPC boundary
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    Native,
+    Du,
+    Dust,
+    Mdfind,
+}
+
+// What a backend is able to report about a directory tree
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendCapabilities {
+    // Returns every level of the tree rather than a single layer or a sample
+    pub full_depth: bool,
+    // Can report logical file lengths
+    pub apparent_size: bool,
+    // Can report blocks actually allocated on disk
+    pub allocated_size: bool,
+    // Emits scan-intermediate trees while the scan is still running
+    pub streaming: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendInfo {
+    pub kind: BackendKind,
+    pub name: String,
+    pub available: bool,
+    pub capabilities: BackendCapabilities,
+}
+
+pub trait ScanBackend: Sync {
+    fn kind(&self) -> BackendKind;
+
+    fn name(&self) -> &'static str;
+
+    // Probed at runtime so missing external tools are reported, not assumed
+    fn is_available(&self, app_handle: &tauri::AppHandle) -> bool;
+
+    fn capabilities(&self) -> BackendCapabilities;
+
+    fn scan(
+        &self,
+        path: &Path,
+        app_handle: &tauri::AppHandle,
+        state: &mut ScanState,
+    ) -> Result<FileNode, ScanError>;
+
+    fn info(&self, app_handle: &tauri::AppHandle) -> BackendInfo {
+        BackendInfo {
+            kind: self.kind(),
+            name: self.name().to_string(),
+            available: self.is_available(app_handle),
+            capabilities: self.capabilities(),
+        }
+    }
+}
+
+static BACKENDS: [&dyn ScanBackend; 4] = [&NativeBackend, &DuBackend, &DustBackend, &MdfindBackend];
+
+// Looks up the implementation for a backend kind
+pub fn backend_for(kind: BackendKind) -> &'static dyn ScanBackend {
+    BACKENDS.iter().copied().find(|backend| backend.kind() == kind).unwrap_or(&NativeBackend)
+}
+
+// Backend used when a scan does not ask for one explicitly
+pub fn default_backend(app_handle: &tauri::AppHandle) -> BackendKind {
+    if MdfindBackend.is_available(app_handle) {
+        BackendKind::Mdfind
+    } else {
+        BackendKind::Native
+    }
+}
+
+// Finds an executable by name on PATH
+pub(super) fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).map(|dir| dir.join(program)).find(|candidate| {
+        candidate.metadata().map(|m| m.is_file() && is_executable(&m)).unwrap_or(false)
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    true
+}
+
+// List every scan backend with its availability and capabilities
+#[tauri::command]
+pub fn list_scan_backends(app_handle: tauri::AppHandle) -> Vec<BackendInfo> {
+    BACKENDS.iter().map(|backend| backend.info(&app_handle)).collect()
+}
@@ -0,0 +1,114 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::{
+    lock,
+    size::{ByteSizes, SizeMetric},
+    FileNode,
+};
+
+// Refined previews are larger than progress events, so they go out less often
+const EMIT_INTERVAL: Duration = Duration::from_secs(1);
+
+// The large-file preview of an mdfind scan while a native walk works out the
+// exact totals behind it. Each directory of the preview whose walk has
+// finished shows its real size, with the bytes its listed files do not
+// account for in an "unattributed small files" child.
+pub struct Backfill {
+    preview: FileNode,
+    // Directories of the preview, the only ones whose totals are kept
+    preview_dirs: HashSet<PathBuf>,
+    // Filled in from the walker threads
+    totals: Mutex<HashMap<PathBuf, ByteSizes>>,
+    metric: SizeMetric,
+    last_emit: Mutex<Instant>,
+}
+
+impl Backfill {
+    pub fn new(preview: FileNode, metric: SizeMetric) -> Self {
+        let mut preview_dirs = HashSet::new();
+        collect_dirs(&preview, &mut preview_dirs);
+        Backfill {
+            preview,
+            preview_dirs,
+            totals: Mutex::default(),
+            metric,
+            last_emit: Mutex::new(Instant::now()),
+        }
+    }
+
+    // Records the walked total of a directory; true when it changed the preview
+    pub fn record(&self, dir: &Path, sizes: ByteSizes) -> bool {
+        if !self.preview_dirs.contains(dir) {
+            return false;
+        }
+        lock(&self.totals).insert(dir.to_path_buf(), sizes);
+        true
+    }
+
+    pub fn should_emit(&self) -> bool {
+        let mut last_emit = lock(&self.last_emit);
+        if last_emit.elapsed() < EMIT_INTERVAL {
+            return false;
+        }
+        *last_emit = Instant::now();
+        true
+    }
+
+    // The preview with every walked directory's total filled in
+    pub fn tree(&self) -> FileNode {
+        let totals = lock(&self.totals);
+        let mut root = self.preview.clone();
+        self.fill_in(&mut root, &totals);
+        root
+    }
+
+    fn fill_in(&self, node: &mut FileNode, totals: &HashMap<PathBuf, ByteSizes>) {
+        for child in node.children.iter_mut().flatten() {
+            self.fill_in(child, totals);
+        }
+        let Some(&total) = totals.get(&node.os_path()) else { return };
+
+        let mut unattributed = total;
+        for child in node.children.iter().flatten() {
+            unattributed -= child.sizes();
+        }
+        if unattributed.get(self.metric) > 0 {
+            node.children.get_or_insert_with(Vec::new).push(FileNode::unattributed(unattributed, self.metric));
+        }
+        node.set_sizes(total, self.metric);
+    }
+}
+
+fn collect_dirs(node: &FileNode, dirs: &mut HashSet<PathBuf>) {
+    if node.is_dir {
+        dirs.insert(node.os_path());
+    }
+    for child in node.children.iter().flatten() {
+        collect_dirs(child, dirs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::tree::build_tree;
+
+    #[test]
+    fn walked_totals_fill_in_what_the_preview_missed() {
+        let files = HashMap::from([(PathBuf::from("/r/a/big"), ByteSizes::new(100, 100))]);
+        let backfill = Backfill::new(build_tree(&files, Path::new("/r"), SizeMetric::Apparent), SizeMetric::Apparent);
+        assert!(!backfill.record(Path::new("/r/a/unlisted"), ByteSizes::new(7, 7)));
+        assert!(backfill.record(Path::new("/r/a"), ByteSizes::new(150, 160)));
+
+        let tree = backfill.tree();
+        let a = &tree.children.as_ref().unwrap()[0];
+        assert_eq!(a.sizes(), ByteSizes::new(150, 160));
+        let unattributed = a.children.iter().flatten().find(|child| child.unattributed).unwrap();
+        assert_eq!(unattributed.sizes(), ByteSizes::new(50, 60));
+        // The root has not been walked yet and keeps its preview size
+        assert_eq!(tree.size, 100);
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::size::ByteSizes;
+
+// Global file cache for seeding scans
+#[derive(Debug, Clone)]
+struct CachedFile {
+    path: PathBuf,
+    size: ByteSizes,
+}
+
+static FILE_CACHE: std::sync::OnceLock<Arc<Mutex<Vec<CachedFile>>>> = std::sync::OnceLock::new();
+
+// Add files to cache
+pub fn add_files_to_cache(files: &HashMap<PathBuf, ByteSizes>) {
+    let cache = FILE_CACHE.get_or_init(|| Arc::new(Mutex::new(Vec::new())));
+    if let Ok(mut cache_guard) = cache.lock() {
+        // Add new files to cache
+        for (path, size) in files {
+            cache_guard.push(CachedFile { 
+                path: path.clone(), 
+                size: *size 
+            });
+        }
+
+        // Sort by size (largest first) and keep only top 1000
+        cache_guard.sort_by_key(|file| std::cmp::Reverse(file.size.apparent));
+        cache_guard.truncate(1000);
+
+        println!("[CACHE] Updated cache with {} files", cache_guard.len());
+    }
+}
+
+// Get cached files relevant to a directory
+pub fn get_cached_files_for_directory(target_path: &Path) -> HashMap<PathBuf, ByteSizes> {
+    let cache = FILE_CACHE.get_or_init(|| Arc::new(Mutex::new(Vec::new())));
+    let mut relevant_files = HashMap::new();
+
+    if let Ok(cache_guard) = cache.lock() {
+        for cached_file in cache_guard.iter() {
+            if cached_file.path.starts_with(target_path) {
+                relevant_files.insert(cached_file.path.clone(), cached_file.size);
+            }
+        }
+    }
+
+    if !relevant_files.is_empty() {
+        println!("[CACHE] Found {} cached files for directory: {}", 
+            relevant_files.len(), target_path.display());
+    }
+
+    relevant_files
+}
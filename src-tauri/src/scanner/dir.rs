@@ -0,0 +1,363 @@
+// Directory handles for the native walker. On Linux a directory is an open
+// descriptor: its entries are listed with readdir and stat'ed with fstatat
+// relative to it, and subdirectories are opened with openat, so the kernel
+// never resolves a full path again and the walker only builds paths for the
+// nodes it outputs. Elsewhere the same interface is backed by paths.
+
+use std::ffi::OsStr;
+use std::fs;
+
+use super::{
+    mounts,
+    size::{file_id, hard_link_id, ByteSizes},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Directory,
+    Symlink,
+    // Regular files and anything else that is not descended into
+    File,
+}
+
+// What the walker needs to know about an entry
+#[derive(Debug, Clone, Copy)]
+pub struct Stat {
+    pub kind: EntryKind,
+    pub sizes: ByteSizes,
+    pub device: Option<u64>,
+    // (st_dev, st_ino)
+    pub id: Option<(u64, u64)>,
+    // The id again, for a file with more than one hard link
+    pub hard_link: Option<(u64, u64)>,
+}
+
+impl Stat {
+    pub fn of(metadata: &fs::Metadata) -> Self {
+        let file_type = metadata.file_type();
+        let kind = if file_type.is_dir() {
+            EntryKind::Directory
+        } else if file_type.is_symlink() {
+            EntryKind::Symlink
+        } else {
+            EntryKind::File
+        };
+        Stat {
+            kind,
+            sizes: ByteSizes::of(metadata),
+            device: mounts::device_id(metadata),
+            id: file_id(metadata),
+            hard_link: hard_link_id(metadata),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use fd::{Dir, Name, NameBuf};
+#[cfg(not(target_os = "linux"))]
+pub use by_path::{Dir, Name, NameBuf};
+
+#[cfg(target_os = "linux")]
+pub fn name_os_str(name: &Name) -> &OsStr {
+    use std::os::unix::ffi::OsStrExt;
+    OsStr::from_bytes(name.to_bytes())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn name_os_str(name: &Name) -> &OsStr {
+    name
+}
+
+#[cfg(target_os = "linux")]
+mod fd {
+    use std::ffi::{CStr, CString};
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::ptr::NonNull;
+
+    use super::{ByteSizes, EntryKind, Stat};
+
+    pub type Name = CStr;
+    pub type NameBuf = CString;
+
+    pub struct Dir {
+        fd: OwnedFd,
+    }
+
+    impl Dir {
+        pub fn open(path: &Path) -> io::Result<Dir> {
+            let path = CString::new(path.as_os_str().as_bytes())?;
+            open_at(libc::AT_FDCWD, &path, true)
+        }
+
+        // Opens the subdirectory `name`. Without `follow` a symlink in its
+        // place is refused rather than followed.
+        pub fn open_at(&self, name: &Name, follow: bool) -> io::Result<Dir> {
+            open_at(self.fd.as_raw_fd(), name, follow)
+        }
+
+        // Opens a directory further down by its path from here, without
+        // following a symlink in its place
+        pub fn open_below(&self, path: &Path) -> io::Result<Dir> {
+            let path = CString::new(path.as_os_str().as_bytes())?;
+            open_at(self.fd.as_raw_fd(), &path, false)
+        }
+
+        pub fn stat_at(&self, name: &Name, follow: bool) -> io::Result<Stat> {
+            let flags = if follow { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+            let mut stat: libc::stat64 = unsafe { std::mem::zeroed() };
+            if unsafe { libc::fstatat64(self.fd.as_raw_fd(), name.as_ptr(), &mut stat, flags) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(stat_of(&stat))
+        }
+
+        pub fn entries(&self) -> io::Result<Entries> {
+            // The listing gets a descriptor of its own, which closedir closes
+            let fd = unsafe { libc::fcntl(self.fd.as_raw_fd(), libc::F_DUPFD_CLOEXEC, 0) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            match NonNull::new(unsafe { libc::fdopendir(fd) }) {
+                Some(stream) => Ok(Entries { stream }),
+                None => {
+                    let error = io::Error::last_os_error();
+                    unsafe { libc::close(fd) };
+                    Err(error)
+                }
+            }
+        }
+    }
+
+    fn open_at(dir: RawFd, name: &CStr, follow: bool) -> io::Result<Dir> {
+        let mut flags = libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC | libc::O_NOCTTY;
+        if !follow {
+            flags |= libc::O_NOFOLLOW;
+        }
+        let fd = unsafe { libc::openat(dir, name.as_ptr(), flags) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Dir { fd: unsafe { OwnedFd::from_raw_fd(fd) } })
+    }
+
+    fn stat_of(stat: &libc::stat64) -> Stat {
+        let kind = match stat.st_mode & libc::S_IFMT {
+            libc::S_IFDIR => EntryKind::Directory,
+            libc::S_IFLNK => EntryKind::Symlink,
+            _ => EntryKind::File,
+        };
+        let id = (stat.st_dev, stat.st_ino);
+        Stat {
+            kind,
+            sizes: ByteSizes::new(stat.st_size as u64, stat.st_blocks as u64 * 512),
+            device: Some(stat.st_dev),
+            id: Some(id),
+            hard_link: (stat.st_nlink > 1).then_some(id),
+        }
+    }
+
+    // The names in a directory, other than "." and ".."
+    pub struct Entries {
+        stream: NonNull<libc::DIR>,
+    }
+
+    impl Entries {
+        // Borrows the name from the stream until the next call, so listing
+        // allocates nothing. The kind is what readdir reports, when the
+        // filesystem reports one.
+        pub fn next_entry(&mut self) -> Option<io::Result<(&Name, Option<EntryKind>)>> {
+            loop {
+                // readdir only reports errors through errno
+                unsafe { *libc::__errno_location() = 0 };
+                let entry = unsafe { libc::readdir64(self.stream.as_ptr()) };
+                if entry.is_null() {
+                    let error = io::Error::last_os_error();
+                    return (error.raw_os_error() != Some(0)).then_some(Err(error));
+                }
+                let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+                if name.to_bytes() != b"." && name.to_bytes() != b".." {
+                    let kind = match unsafe { (*entry).d_type } {
+                        libc::DT_UNKNOWN => None,
+                        libc::DT_DIR => Some(EntryKind::Directory),
+                        libc::DT_LNK => Some(EntryKind::Symlink),
+                        _ => Some(EntryKind::File),
+                    };
+                    return Some(Ok((name, kind)));
+                }
+            }
+        }
+    }
+
+    impl Drop for Entries {
+        fn drop(&mut self) {
+            unsafe { libc::closedir(self.stream.as_ptr()) };
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod by_path {
+    use std::ffi::{OsStr, OsString};
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    use super::{EntryKind, Stat};
+
+    pub type Name = OsStr;
+    pub type NameBuf = OsString;
+
+    pub struct Dir {
+        path: PathBuf,
+    }
+
+    impl Dir {
+        pub fn open(path: &Path) -> io::Result<Dir> {
+            Ok(Dir { path: path.to_path_buf() })
+        }
+
+        pub fn open_at(&self, name: &Name, _follow: bool) -> io::Result<Dir> {
+            Ok(Dir { path: self.path.join(name) })
+        }
+
+        pub fn open_below(&self, path: &Path) -> io::Result<Dir> {
+            Ok(Dir { path: self.path.join(path) })
+        }
+
+        pub fn stat_at(&self, name: &Name, follow: bool) -> io::Result<Stat> {
+            let path = self.path.join(name);
+            let metadata = if follow { fs::metadata(&path) } else { fs::symlink_metadata(&path) };
+            metadata.map(|metadata| Stat::of(&metadata))
+        }
+
+        pub fn entries(&self) -> io::Result<Entries> {
+            Ok(Entries { read_dir: fs::read_dir(&self.path)?, current: OsString::new() })
+        }
+    }
+
+    pub struct Entries {
+        read_dir: fs::ReadDir,
+        current: OsString,
+    }
+
+    impl Entries {
+        pub fn next_entry(&mut self) -> Option<io::Result<(&Name, Option<EntryKind>)>> {
+            match self.read_dir.next()? {
+                Ok(entry) => {
+                    self.current = entry.file_name();
+                    let kind = entry.file_type().ok().map(|file_type| {
+                        if file_type.is_dir() {
+                            EntryKind::Directory
+                        } else if file_type.is_symlink() {
+                            EntryKind::Symlink
+                        } else {
+                            EntryKind::File
+                        }
+                    });
+                    Some(Ok((&self.current, kind)))
+                }
+                Err(e) => Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+
+    #[cfg(target_os = "linux")]
+    fn name(text: &str) -> NameBuf {
+        NameBuf::new(text).unwrap()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn name(text: &str) -> NameBuf {
+        NameBuf::from(text)
+    }
+
+    fn test_dir(label: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("dir-{}-{}", label, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sub/deeper")).unwrap();
+        fs::write(root.join("file"), vec![0u8; 3000]).unwrap();
+        root
+    }
+
+    #[test]
+    fn listing_names_the_entries_but_not_dot_and_dot_dot() {
+        let root = test_dir("list");
+        let dir = Dir::open(&root).unwrap();
+        let mut listed = Vec::new();
+        let mut entries = dir.entries().unwrap();
+        while let Some(entry) = entries.next_entry() {
+            let (entry_name, kind) = entry.unwrap();
+            listed.push((name_os_str(entry_name).to_os_string(), kind));
+        }
+        drop(entries);
+        fs::remove_dir_all(&root).unwrap();
+
+        listed.sort_by(|a, b| a.0.cmp(&b.0));
+        let names: Vec<_> = listed.iter().map(|(entry_name, _)| entry_name.to_str().unwrap()).collect();
+        assert_eq!(names, ["file", "sub"]);
+        // The kind is optional, but when reported it must be right
+        assert!(matches!(listed[0].1, None | Some(EntryKind::File)));
+        assert!(matches!(listed[1].1, None | Some(EntryKind::Directory)));
+    }
+
+    #[test]
+    fn stat_at_matches_the_metadata_of_the_path() {
+        let root = test_dir("stat");
+        let dir = Dir::open(&root).unwrap();
+        let stat = dir.stat_at(&name("file"), false).unwrap();
+        let sub = dir.open_at(&name("sub"), false).unwrap().stat_at(&name("deeper"), false).unwrap();
+        let metadata = fs::metadata(root.join("file")).unwrap();
+        let missing = dir.stat_at(&name("missing"), false);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(stat.kind, EntryKind::File);
+        assert_eq!(stat.sizes, ByteSizes::of(&metadata));
+        assert_eq!(stat.id, file_id(&metadata));
+        assert_eq!(stat.hard_link, None);
+        assert_eq!(sub.kind, EntryKind::Directory);
+        assert_eq!(missing.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn open_below_reaches_a_directory_further_down() {
+        let root = test_dir("below");
+        fs::write(root.join("sub/deeper/inside"), b"x").unwrap();
+        let dir = Dir::open(&root).unwrap();
+        let deeper = dir.open_below(Path::new("sub/deeper")).unwrap();
+        let inside = deeper.stat_at(&name("inside"), false);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(inside.unwrap().sizes.apparent, 1);
+    }
+
+    // Only descriptors can refuse a symlink; by path it is always followed
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn symlinks_are_opened_only_when_followed() {
+        let root = test_dir("symlink");
+        std::os::unix::fs::symlink(root.join("sub"), root.join("link")).unwrap();
+        let dir = Dir::open(&root).unwrap();
+        let refused = dir.open_at(&name("link"), false).is_err();
+        let followed = dir.open_at(&name("link"), true).is_ok();
+        let below = dir.open_below(Path::new("link/deeper")).is_ok();
+        let link = dir.stat_at(&name("link"), false).unwrap();
+        let target = dir.stat_at(&name("link"), true).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(refused);
+        assert!(followed);
+        // Only the last component is protected; the walker opens one level at a time
+        assert!(below);
+        assert_eq!(link.kind, EntryKind::Symlink);
+        assert_eq!(target.kind, EntryKind::Directory);
+    }
+}
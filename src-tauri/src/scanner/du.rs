@@ -0,0 +1,351 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use super::{
+    backend::{find_in_path, BackendCapabilities, BackendKind, ScanBackend},
+    error::{ScanError, SkippedPath},
+    format_size, mounts,
+    options::SymlinkPolicy,
+    root_display_name,
+    process::{nul_records, path_from_bytes, run_command, ToolOutput},
+    size::ByteSizes,
+    symlink_target, FileNode, ScanState,
+};
+
+pub struct DuBackend;
+
+impl ScanBackend for DuBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Du
+    }
+
+    fn name(&self) -> &'static str {
+        "du"
+    }
+
+    fn is_available(&self, _app_handle: &tauri::AppHandle) -> bool {
+        is_gnu_du()
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            full_depth: false,
+            apparent_size: true,
+            allocated_size: true,
+            streaming: false,
+        }
+    }
+
+    fn scan(
+        &self,
+        path: &Path,
+        app_handle: &tauri::AppHandle,
+        state: &mut ScanState,
+    ) -> Result<FileNode, ScanError> {
+        scan_directory_with_du(path, app_handle, state)
+    }
+}
+
+// Fallback scanning using du command
+pub fn scan_directory_with_du(
+    path: &Path,
+    app_handle: &tauri::AppHandle,
+    state: &mut ScanState,
+) -> Result<FileNode, ScanError> {
+    let path_str = path.to_string_lossy();
+    
+    println!("[DU] Running du command on: {}", path.display());
+    state.refuse_skip_rules(path, false)?;
+    
+    // du reports one kind of size per run, so allocated and apparent sizes
+    // come from two passes over the same entries
+    let output = run_du(path, false, state)?;
+    let apparent_output = run_du(path, true, state)?;
+    let (_, apparent_unit) = du_size_args(true);
+    let apparent_sizes: HashMap<PathBuf, u64> = nul_records(&apparent_output.stdout)
+        .filter_map(parse_du_record)
+        .map(|(size, entry_path)| (entry_path, size * apparent_unit))
+        .collect();
+    let (_, allocated_unit) = du_size_args(false);
+    
+    // Anything du could not read is reported rather than silently missing
+    let mut skipped_paths = HashSet::new();
+    for (skipped_path, message) in parse_du_errors(&output.stderr) {
+        if skipped_path.starts_with(path) {
+            state.record_skipped(SkippedPath::from_tool_message(&skipped_path, &message));
+            skipped_paths.insert(skipped_path);
+        }
+    }
+    
+    let mut entries: Vec<(ByteSizes, PathBuf)> = nul_records(&output.stdout)
+        .filter_map(parse_du_record)
+        .filter(|(_, entry_path)| entry_path.as_path() != path)
+        .map(|(size, entry_path)| {
+            let allocated = size * allocated_unit;
+            let apparent = apparent_sizes.get(&entry_path).copied().unwrap_or(allocated);
+            (ByteSizes::new(apparent, allocated), entry_path)
+        })
+        .collect();
+    let metric = state.metric();
+    entries.sort_by_key(|(sizes, _)| std::cmp::Reverse(sizes.get(metric)));
+    println!("[DU] du reported {} entries", entries.len());
+    
+    let mut children = Vec::new();
+    // du counts the directory's own entry in its total, so the root's starts
+    // with it
+    let own_blocks = fs::metadata(path).map(|m| ByteSizes::of(&m)).unwrap_or_default();
+    let mut total_size = own_blocks;
+    
+    for (idx, (sizes, full_path)) in entries.into_iter().enumerate() {
+        // Check for cancellation or timeout
+        if let Err(e) = state.check_interrupted() {
+            println!("[DU] Scan stopped during processing: {}", e);
+            return Err(e);
+        }
+        
+        let size = sizes.get(metric);
+        let name = full_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let metadata = fs::symlink_metadata(&full_path).ok();
+        let is_dir = metadata.as_ref().is_some_and(|m| m.is_dir());
+        if let Some(node) = metadata.as_ref().filter(|_| is_dir).and_then(|m| state.mount_point_node(&full_path, mounts::device_id(m))) {
+            children.push(node);
+            continue;
+        }
+        
+        // Update progress. Only files go into a partial tree, where every
+        // entry becomes a file.
+        if is_dir {
+            state.counters.add_directory();
+            state.counters.add_bytes(size);
+        } else {
+            state.counters.add_file(size);
+            state.record_file(&full_path, sizes);
+        }
+        if idx % 5 == 0 {
+            state.emit(app_handle, &path_str);
+        }
+        
+        if idx < 5 {
+            println!("[DU] Item {}: {} ({})", idx, name, format_size(size));
+        }
+        
+        let mut node = FileNode::new(name, &full_path, is_dir, sizes, metric);
+        node.inaccessible = skipped_paths.contains(&full_path);
+        node.symlink_target = symlink_target(&full_path);
+        children.push(node);
+        
+        total_size += sizes;
+    }
+    
+    // du leaves mount points out of its output altogether under -x or
+    // --exclude
+    for node in state.mount_points_in(path) {
+        if !children.iter().any(|child| child.path == node.path) {
+            children.push(node);
+        }
+    }
+    
+    println!("[DU] Found {} children before filtering", children.len());
+    
+    // If we got no results from du, try a different approach
+    if children.is_empty() {
+        println!("[DU] WARNING: No results from du command, trying ls approach");
+        // Try listing directory contents directly
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries.collect::<Vec<_>>(),
+            Err(e) => {
+                state.skip(path, true, &e);
+                Vec::new()
+            }
+        };
+        for (idx, entry) in entries.into_iter().enumerate() {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    state.skip(path, true, &e);
+                    break;
+                }
+            };
+            let name = entry.file_name().to_string_lossy().to_string();
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    children.push(state.skip(&entry.path(), false, &e));
+                    continue;
+                }
+            };
+            
+            let sizes = if metadata.is_dir() {
+                // For directories, estimate size (we'll scan them later)
+                ByteSizes::new(1024 * 1024, 1024 * 1024) // 1MB placeholder
+            } else {
+                ByteSizes::of(&metadata)
+            };
+            
+            children.push(FileNode::new(
+                name,
+                &entry.path(),
+                metadata.is_dir(),
+                sizes,
+                metric,
+            ));
+            
+            total_size += sizes;
+            
+            // Update progress
+            if metadata.is_dir() {
+                state.counters.add_directory();
+            } else {
+                state.counters.add_file(sizes.get(metric));
+            }
+            if idx % 5 == 0 || state.should_emit() {
+                state.emit(app_handle, &path_str);
+            }
+        }
+    }
+    
+    // Sort by size (largest first)
+    children.sort_by_key(|c| std::cmp::Reverse(c.size));
+    
+    println!("[DU] Scan complete: found {} items, total size: {}", children.len(), format_size(total_size.get(metric)));
+    
+    let name = root_display_name(path);
+
+    let mut root = FileNode::new(name, path, true, total_size, metric);
+    root.children = if children.is_empty() { None } else { Some(children) };
+    root.add_own_blocks(own_blocks, metric);
+    state.prune_excluded(&mut root);
+    Ok(root)
+}
+
+// The backend relies on GNU du options (-0, -b, --exclude) that BSD du, as
+// shipped with macOS, does not have. Probed once per run.
+fn is_gnu_du() -> bool {
+    static GNU_DU: OnceLock<bool> = OnceLock::new();
+    *GNU_DU.get_or_init(|| {
+        find_in_path("du").is_some()
+            && Command::new("du")
+                .arg("--version")
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output()
+                .is_ok_and(|output| output.status.success() && output.stdout.windows(3).any(|w| w == b"GNU"))
+    })
+}
+
+// du flags selecting a kind of size, and the unit its numbers are then in
+fn du_size_args(apparent: bool) -> (&'static [&'static str], u64) {
+    if apparent {
+        (&["-b"], 1)
+    } else {
+        (&["-k"], 1024)
+    }
+}
+
+// One record per direct child (files included), NUL-terminated so any byte
+// sequence in a name survives. Output is "<size>\t<path>\0". LC_ALL=C keeps
+// the error messages on stderr in a parseable form.
+fn run_du(path: &Path, apparent: bool, state: &ScanState) -> Result<ToolOutput, ScanError> {
+    let (size_args, _) = du_size_args(apparent);
+    let mut command = Command::new("du");
+    command.args(size_args).args(["-a", "-d", "1", "-0"]);
+    if state.root_device.is_some() {
+        command.arg("-x");
+    }
+    // du cannot tell where a link leads before following it, so the
+    // within-root policy is treated like never following
+    if state.options.symlink_policy == SymlinkPolicy::Always {
+        command.arg("-L");
+    }
+    for mount_point in state.excluded_mounts_below(path) {
+        command.arg(exclude_arg(&mount_point));
+    }
+    run_command(command.arg("--").arg(path).env("LC_ALL", "C"), state)
+}
+
+// GNU du matches --exclude patterns against the full path with fnmatch, so
+// wildcard characters in the mount point are escaped
+fn exclude_arg(path: &Path) -> std::ffi::OsString {
+    let mut pattern = Vec::new();
+    for byte in path.as_os_str().as_encoded_bytes() {
+        if matches!(byte, b'*' | b'?' | b'[' | b'\\') {
+            pattern.push(b'\\');
+        }
+        pattern.push(*byte);
+    }
+    let mut arg = std::ffi::OsString::from("--exclude=");
+    arg.push(path_from_bytes(&pattern));
+    arg
+}
+
+// Parses one "<size>\t<path>" du record, keeping the path bytes exact
+fn parse_du_record(record: &[u8]) -> Option<(u64, PathBuf)> {
+    let tab = record.iter().position(|b| *b == b'\t')?;
+    let size = std::str::from_utf8(&record[..tab]).ok()?.trim().parse().ok()?;
+    Some((size, path_from_bytes(&record[tab + 1..])))
+}
+
+// Pulls (path, reason) pairs out of du's stderr. Most messages quote the path
+// ("du: cannot read directory '<path>': <reason>"), a few print it bare
+// ("du: <path>: <reason>").
+fn parse_du_errors(stderr: &[u8]) -> Vec<(PathBuf, String)> {
+    stderr
+        .split(|b| *b == b'\n')
+        .filter_map(|line| {
+            let line = line.strip_prefix(b"du: ")?;
+            let (path, reason) = if line.starts_with(b"cannot ") {
+                let open = line.iter().position(|b| *b == b'\'' || *b == b'"')?;
+                let close = rfind(line, &[line[open], b':', b' '])?;
+                (line.get(open + 1..close)?, &line[close + 3..])
+            } else {
+                let split = rfind(line, b": ")?;
+                (&line[..split], &line[split + 2..])
+            };
+            Some((path_from_bytes(path), String::from_utf8_lossy(reason).into_owned()))
+        })
+        .collect()
+}
+
+fn rfind(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).rposition(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn du_record_keeps_the_whole_path() {
+        assert_eq!(parse_du_record(b"4096\t/a b/c\td"), Some((4096, PathBuf::from("/a b/c\td"))));
+        assert_eq!(parse_du_record(b"12\t/x"), Some((12, PathBuf::from("/x"))));
+        assert_eq!(parse_du_record(b"no size\t/x"), None);
+        assert_eq!(parse_du_record(b"4096 /x"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn du_record_keeps_non_utf8_path_bytes() {
+        use std::os::unix::ffi::OsStrExt;
+        let (_, path) = parse_du_record(b"1\t/bad\xff").unwrap();
+        assert_eq!(path.as_os_str().as_bytes(), b"/bad\xff");
+    }
+
+    #[test]
+    fn du_errors_quoted_and_bare() {
+        let stderr = b"du: cannot read directory '/root/secret': Permission denied\n\
+            du: cannot access \"/tmp/it's\": No such file or directory\n\
+            du: /mnt/stale: Stale file handle\n\
+            something else\n";
+        assert_eq!(
+            parse_du_errors(stderr),
+            [
+                (PathBuf::from("/root/secret"), "Permission denied".to_string()),
+                (PathBuf::from("/tmp/it's"), "No such file or directory".to_string()),
+                (PathBuf::from("/mnt/stale"), "Stale file handle".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+use super::{
+    backend::{find_in_path, BackendCapabilities, BackendKind, ScanBackend},
+    error::{ScanError, SkippedPath},
+    format_size,
+    options::{HiddenEntries, SymlinkPolicy},
+    process::run_command,
+    root_display_name,
+    size::{ByteSizes, SizeMetric},
+    symlink_target, FileNode, ScanState,
+};
+
+// Upper bound on the rows dust reports; it keeps the largest entries
+const MAX_DUST_ENTRIES: u32 = 100_000;
+
+// Lines --print-errors ends dust's stderr with, one per kind of error, and
+// the OS description each stands for
+const DUST_ERRORS: [(&str, &str); 3] = [
+    ("Did not have permissions for directories: ", "Permission denied"),
+    ("No such file or directory: ", "No such file or directory"),
+    ("Unknown Error: ", "Unknown error"),
+];
+
+pub struct DustBackend;
+
+impl ScanBackend for DustBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Dust
+    }
+
+    fn name(&self) -> &'static str {
+        "dust"
+    }
+
+    fn is_available(&self, _app_handle: &tauri::AppHandle) -> bool {
+        dust_binary().is_some()
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            full_depth: true,
+            apparent_size: true,
+            allocated_size: true,
+            streaming: false,
+        }
+    }
+
+    fn scan(
+        &self,
+        path: &Path,
+        app_handle: &tauri::AppHandle,
+        state: &mut ScanState,
+    ) -> Result<FileNode, ScanError> {
+        scan_directory_with_dust(path, app_handle, state)
+    }
+}
+
+// Locates a dust that runs on this machine, preferring the sidecar bundled
+// next to the app's executable over one on PATH
+fn dust_binary() -> Option<&'static Path> {
+    static DUST: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DUST.get_or_init(|| {
+        let bundled = std::env::current_exe()
+            .ok()
+            .and_then(|exe| Some(exe.parent()?.join(format!("dust{}", std::env::consts::EXE_SUFFIX))));
+        bundled.into_iter().chain(find_in_path("dust")).find(|candidate| runs(candidate))
+    })
+    .as_deref()
+}
+
+// A binary built for another platform fails to start, so checking the
+// executable bit is not enough
+fn runs(program: &Path) -> bool {
+    Command::new(program)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+// Scans the tree with dust's JSON output, keeping every nested level
+pub fn scan_directory_with_dust(
+    path: &Path,
+    app_handle: &tauri::AppHandle,
+    state: &mut ScanState,
+) -> Result<FileNode, ScanError> {
+    let dust_path = dust_binary()
+        .ok_or_else(|| ScanError::backend_unavailable(path, BackendKind::Dust))?;
+    // A tree with every level and entry can have any of them pruned
+    let limits = state.options.tree_limits();
+    if limits.depth.is_some() || limits.max_children.is_some() {
+        state.refuse_skip_rules(path, true)?;
+    }
+    println!("[DUST] Using dust at {} for scanning", dust_path.display());
+
+    // dust reports one kind of size per run, so the tree is built from a run
+    // with the selected metric while a second run alongside it supplies the
+    // other size for the same paths
+    let metric = state.metric();
+    let (primary, secondary) = {
+        let state = &*state;
+        std::thread::scope(|scope| {
+            let secondary = scope.spawn(|| {
+                let apparent = metric == SizeMetric::Allocated;
+                run_command(&mut dust_command(dust_path, path, apparent, state), state)
+            });
+            let apparent = metric == SizeMetric::Apparent;
+            let primary = run_command(&mut dust_command(dust_path, path, apparent, state), state);
+            let secondary = secondary
+                .join()
+                .unwrap_or_else(|_| Err(ScanError::other(path, "dust worker panicked")));
+            (primary, secondary)
+        })
+    };
+
+    let primary = primary?;
+    let json_data = parse_dust_json(&primary.stdout, path)?;
+    let mut other_sizes = HashMap::new();
+    collect_sizes(&parse_dust_json(&secondary?.stdout, path)?, path, &mut other_sizes);
+
+    // Both runs hit the same unreadable paths, so one run's report covers them
+    let mut skipped_paths = HashSet::new();
+    for (skipped_path, message) in parse_dust_errors(&primary.stderr) {
+        if skipped_path.starts_with(path) {
+            state.record_skipped(SkippedPath::from_tool_message(&skipped_path, message));
+            skipped_paths.insert(skipped_path);
+        }
+    }
+
+    let tree = DustTree { root_path: path, other_sizes: &other_sizes, skipped_paths: &skipped_paths };
+    let mut root = node_from_json(&json_data, &tree, app_handle, state)
+        .ok_or_else(|| ScanError::other(path, "dust output did not describe a directory tree"))?;
+    root.name = root_display_name(path);
+    root.set_path(path);
+    root.is_dir = true;
+    state.prune_excluded(&mut root);
+
+    println!(
+        "[DUST] Dust scan complete: {} items, total size: {}",
+        state.counters.items(),
+        format_size(root.size)
+    );
+
+    Ok(root)
+}
+
+fn dust_command(dust_path: &Path, path: &Path, apparent: bool, state: &ScanState) -> Command {
+    let mut command = Command::new(dust_path);
+    let limits = state.options.tree_limits();
+    if let Some(depth) = limits.depth {
+        command.arg("-d").arg(depth.to_string());
+    }
+    if apparent {
+        command.arg("-s");
+    }
+    if state.root_device.is_some() {
+        command.arg("-x");
+    }
+    if state.options.hidden == HiddenEntries::Hide {
+        command.arg("--ignore-hidden");
+    }
+    // Like du, dust cannot limit following to links inside the root
+    if state.options.symlink_policy == SymlinkPolicy::Always {
+        command.arg("-L");
+    }
+    for mount_point in state.excluded_mounts_below(path) {
+        command.arg("-X").arg(mount_point);
+    }
+
+    // -o b reports exact byte counts and -P keeps the progress spinner out of
+    // the JSON stream
+    // dust keeps only its largest entries; a complete tree needs all of them
+    let max_entries = if limits.max_children.is_none() { u32::MAX } else { MAX_DUST_ENTRIES };
+    command
+        .arg("-n")
+        .arg(max_entries.to_string())
+        .args(["-j", "-o", "b", "-P", "--print-errors"])
+        .arg(path);
+    command
+}
+
+// The tree is printed as a single JSON line
+fn parse_dust_json(output: &[u8], path: &Path) -> Result<serde_json::Value, ScanError> {
+    let json_line = output
+        .split(|b| *b == b'\n')
+        .map(String::from_utf8_lossy)
+        .find(|line| line.trim_start().starts_with('{'))
+        .unwrap_or_default();
+    println!("[DUST] JSON length: {} chars", json_line.len());
+
+    serde_json::from_str::<serde_json::Value>(&json_line)
+        .map_err(|e| ScanError::other(path, format!("Failed to parse dust output: {}", e)))
+}
+
+// dust reports paths relative to the working directory unless given an
+// absolute root
+fn dust_node_path(name: &str, root_path: &Path) -> PathBuf {
+    match name.strip_prefix("./") {
+        Some(relative) => root_path.join(relative),
+        None => PathBuf::from(name),
+    }
+}
+
+// Flattens a dust tree into path -> bytes
+fn collect_sizes(item: &serde_json::Value, root_path: &Path, sizes: &mut HashMap<PathBuf, u64>) {
+    let Some(name) = item.get("name").and_then(|n| n.as_str()) else { return };
+    if let Some(size) = item.get("size").and_then(parse_byte_size) {
+        sizes.insert(dust_node_path(name, root_path), size);
+    }
+    for child in item.get("children").and_then(|c| c.as_array()).into_iter().flatten() {
+        collect_sizes(child, root_path, sizes);
+    }
+}
+
+// What the JSON tree is read against
+struct DustTree<'a> {
+    root_path: &'a Path,
+    // Sizes of the metric the tree's run did not report
+    other_sizes: &'a HashMap<PathBuf, u64>,
+    skipped_paths: &'a HashSet<PathBuf>,
+}
+
+// Converts one dust JSON node, and everything below it, into a FileNode
+fn node_from_json(
+    item: &serde_json::Value,
+    tree: &DustTree,
+    app_handle: &tauri::AppHandle,
+    state: &mut ScanState,
+) -> Option<FileNode> {
+    let root_path = tree.root_path;
+    let name = item.get("name").and_then(|n| n.as_str())?;
+    let Some(size) = item.get("size").and_then(parse_byte_size) else {
+        println!("[DUST] Skipping {} with unparseable size: {:?}", name, item.get("size"));
+        return None;
+    };
+
+    let full_path = dust_node_path(name, root_path);
+    let other_size = tree.other_sizes.get(&full_path).copied().unwrap_or(size);
+    let sizes = match state.metric() {
+        SizeMetric::Apparent => ByteSizes::new(size, other_size),
+        SizeMetric::Allocated => ByteSizes::new(other_size, size),
+    };
+
+    let mut children: Vec<FileNode> = item
+        .get("children")
+        .and_then(|c| c.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|child| node_from_json(child, tree, app_handle, state))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // dust lists skipped mount points as empty directories, if at all
+    let mount_points = state.mount_points_in(&full_path);
+    children.retain(|child| !mount_points.iter().any(|point| point.path == child.path));
+    children.extend(mount_points);
+    children.sort_by_key(|c| std::cmp::Reverse(c.size));
+
+    // dust keeps only its largest entries, so a directory's total can exceed
+    // what its listed children and its own blocks add up to. The rest goes
+    // into an "Other" child so the children still account for the total.
+    let own_blocks = fs::symlink_metadata(&full_path).map(|m| ByteSizes::of(&m)).unwrap_or_default();
+    if !children.is_empty() {
+        let mut untracked = sizes;
+        untracked -= own_blocks;
+        for child in &children {
+            untracked -= child.sizes();
+        }
+        if untracked.get(state.metric()) > 0 {
+            children.push(FileNode::untracked(untracked, state.metric()));
+        }
+    }
+
+    // Leaves at the depth limit are still directories when they have contents
+    let is_dir = !children.is_empty()
+        || match state.options.symlink_policy {
+            SymlinkPolicy::Always => full_path.is_dir(),
+            _ => fs::symlink_metadata(&full_path).is_ok_and(|m| m.is_dir()),
+        };
+
+    // Directory sizes include their children, so only leaves add bytes
+    if is_dir {
+        state.counters.add_directory();
+        if children.is_empty() {
+            state.counters.add_bytes(size);
+        }
+    } else {
+        state.counters.add_file(size);
+    }
+    if state.should_emit() {
+        state.emit(app_handle, &full_path.parent().unwrap_or(root_path).to_string_lossy());
+    }
+
+    let mut node = FileNode::new(
+        full_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| name.to_string()),
+        &full_path,
+        is_dir,
+        sizes,
+        state.metric(),
+    );
+    node.children = if children.is_empty() { None } else { Some(children) };
+    node.add_own_blocks(own_blocks, state.metric());
+    node.inaccessible = tree.skipped_paths.contains(&full_path);
+    node.symlink_target = symlink_target(&full_path);
+    Some(node)
+}
+
+// Pulls (path, reason) pairs out of dust's error summary. Paths are joined by
+// ", " and dust is always given an absolute root, so a piece that does not
+// start with "/" is still part of the previous path.
+fn parse_dust_errors(stderr: &[u8]) -> Vec<(PathBuf, &'static str)> {
+    let stderr = String::from_utf8_lossy(stderr);
+    let mut errors = Vec::new();
+    for line in stderr.lines() {
+        let Some((list, reason)) =
+            DUST_ERRORS.iter().find_map(|(prefix, reason)| Some((line.strip_prefix(prefix)?, *reason)))
+        else {
+            continue;
+        };
+        let mut paths: Vec<String> = Vec::new();
+        for piece in list.split(", ") {
+            match paths.last_mut() {
+                Some(last) if !piece.starts_with('/') => {
+                    last.push_str(", ");
+                    last.push_str(piece);
+                }
+                _ => paths.push(piece.to_string()),
+            }
+        }
+        errors.extend(paths.into_iter().map(|path| (PathBuf::from(path), reason)));
+    }
+    errors
+}
+
+// Reads an exact byte count from dust's "-o b" output ("1234B") or a number
+fn parse_byte_size(value: &serde_json::Value) -> Option<u64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => {
+            let s = s.trim();
+            s.strip_suffix('B').unwrap_or(s).trim().parse().ok()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dust_errors_split_into_paths() {
+        let stderr = b"Did not have permissions for directories: /a/secret, /a/b, c, /a/d\n\
+            No such file or directory: /a/gone\n\
+            Did not have permissions for all directories (add --print-errors to see errors)\n";
+        assert_eq!(
+            parse_dust_errors(stderr),
+            [
+                (PathBuf::from("/a/secret"), "Permission denied"),
+                (PathBuf::from("/a/b, c"), "Permission denied"),
+                (PathBuf::from("/a/d"), "Permission denied"),
+                (PathBuf::from("/a/gone"), "No such file or directory"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::backend::{backend_for, BackendKind};
+
+// Errors returned to the frontend. Serialized with a "kind" tag so the UI can
+// react to each case; every variant names the offending path and carries the
+// OS error code when there is one.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ScanError {
+    NotFound { path: String, code: Option<i32> },
+    PermissionDenied { path: String, code: Option<i32> },
+    Cancelled { path: String, code: Option<i32> },
+    BackendUnavailable { path: String, code: Option<i32>, backend: BackendKind },
+    Timeout { path: String, code: Option<i32>, limit_ms: u64 },
+    AlreadyRunning { path: String, code: Option<i32>, scan_id: String },
+    // No scan is registered under the ID; `path` is empty
+    UnknownScan { path: String, code: Option<i32>, scan_id: String },
+    // Not possible on this platform, or with `backend` when one is named
+    Unsupported {
+        path: String,
+        code: Option<i32>,
+        operation: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        backend: Option<BackendKind>,
+    },
+    Io { path: String, code: Option<i32>, message: String },
+}
+
+impl ScanError {
+    // Classifies an I/O error raised while touching `path`
+    pub fn io(path: &Path, error: &io::Error) -> Self {
+        let path = path.to_string_lossy().to_string();
+        let code = error.raw_os_error();
+        match error.kind() {
+            io::ErrorKind::NotFound => ScanError::NotFound { path, code },
+            io::ErrorKind::PermissionDenied => ScanError::PermissionDenied { path, code },
+            _ => ScanError::Io { path, code, message: error.to_string() },
+        }
+    }
+
+    // An I/O-class failure with no OS error behind it (bad tool output etc.)
+    pub fn other(path: &Path, message: impl Into<String>) -> Self {
+        ScanError::Io { path: path.to_string_lossy().to_string(), code: None, message: message.into() }
+    }
+
+    pub fn cancelled(path: &Path) -> Self {
+        ScanError::Cancelled { path: path.to_string_lossy().to_string(), code: None }
+    }
+
+    pub fn timeout(path: &Path, limit_ms: u64) -> Self {
+        ScanError::Timeout { path: path.to_string_lossy().to_string(), code: None, limit_ms }
+    }
+
+    pub fn backend_unavailable(path: &Path, backend: BackendKind) -> Self {
+        ScanError::BackendUnavailable { path: path.to_string_lossy().to_string(), code: None, backend }
+    }
+
+    pub fn unsupported_by(path: &Path, backend: BackendKind, operation: &str) -> Self {
+        ScanError::Unsupported {
+            path: path.to_string_lossy().to_string(),
+            code: None,
+            operation: operation.to_string(),
+            backend: Some(backend),
+        }
+    }
+
+    pub fn unknown_scan(scan_id: &str) -> Self {
+        ScanError::UnknownScan { path: String::new(), code: None, scan_id: scan_id.to_string() }
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::NotFound { path, .. } => write!(f, "{} does not exist", path),
+            ScanError::PermissionDenied { path, .. } => write!(f, "Permission denied: {}", path),
+            ScanError::Cancelled { path, .. } => write!(f, "Scan of {} was cancelled", path),
+            ScanError::BackendUnavailable { backend, .. } => {
+                write!(f, "Scan backend '{}' is not available", backend_for(*backend).name())
+            }
+            ScanError::Timeout { path, limit_ms, .. } => {
+                write!(f, "Scan of {} timed out after {:.1}s", path, *limit_ms as f64 / 1000.0)
+            }
+            ScanError::AlreadyRunning { scan_id, .. } => {
+                write!(f, "Scan '{}' is already running", scan_id)
+            }
+            ScanError::UnknownScan { scan_id, .. } => write!(f, "Unknown scan: {}", scan_id),
+            ScanError::Unsupported { operation, backend: Some(backend), .. } => {
+                write!(f, "{} is not supported by the {} backend", operation, backend_for(*backend).name())
+            }
+            ScanError::Unsupported { operation, .. } => {
+                write!(f, "{} is not supported on this platform", operation)
+            }
+            ScanError::Io { path, message, .. } => write!(f, "{}: {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+// Why part of the tree was left out of a scan
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipReason {
+    PermissionDenied,
+    // Too many levels of symbolic links (ELOOP)
+    Loop,
+    // Listed by the parent but gone by the time it was read
+    Vanished,
+    Io,
+}
+
+// A path the scan could not read, reported alongside the tree
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedPath {
+    pub path: String,
+    pub reason: SkipReason,
+    pub code: Option<i32>,
+    pub message: String,
+}
+
+impl SkippedPath {
+    pub fn from_io(path: &Path, error: &io::Error) -> Self {
+        let code = error.raw_os_error();
+        let reason = match error.kind() {
+            io::ErrorKind::PermissionDenied => SkipReason::PermissionDenied,
+            io::ErrorKind::NotFound => SkipReason::Vanished,
+            _ if is_loop_error(code) => SkipReason::Loop,
+            _ => SkipReason::Io,
+        };
+        SkippedPath {
+            path: path.to_string_lossy().to_string(),
+            reason,
+            code,
+            message: error.to_string(),
+        }
+    }
+
+    // Rebuilds a skipped entry from an external tool's error text, matching it
+    // against the OS descriptions of the errors tools commonly hit
+    pub fn from_tool_message(path: &Path, message: &str) -> Self {
+        match known_os_error(message) {
+            Some(error) => SkippedPath::from_io(path, &error),
+            None => SkippedPath {
+                path: path.to_string_lossy().to_string(),
+                reason: SkipReason::Io,
+                code: None,
+                message: message.to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(unix)]
+fn known_os_error(message: &str) -> Option<io::Error> {
+    [libc::EACCES, libc::EPERM, libc::ELOOP, libc::ENOENT, libc::EIO, libc::ENAMETOOLONG]
+        .into_iter()
+        .map(io::Error::from_raw_os_error)
+        // io::Error's Display appends " (os error N)" to the strerror text
+        .find(|error| error.to_string().split(" (os error").next() == Some(message))
+}
+
+#[cfg(not(unix))]
+fn known_os_error(_message: &str) -> Option<io::Error> {
+    None
+}
+
+#[cfg(unix)]
+fn is_loop_error(code: Option<i32>) -> bool {
+    code == Some(libc::ELOOP)
+}
+
+#[cfg(not(unix))]
+fn is_loop_error(_code: Option<i32>) -> bool {
+    false
+}
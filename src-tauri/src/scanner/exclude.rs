@@ -0,0 +1,214 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    error::ScanError,
+    options::{HiddenEntries, ScanOptions},
+    paths,
+};
+
+// First bytes of a valid CACHEDIR.TAG (https://bford.info/cachedir/)
+const CACHEDIR_TAG_SIGNATURE: &[u8] = b"Signature: 8a477f597d28d172789f06886806bc55";
+
+// What happens to an excluded subtree
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ExcludeMode {
+    // Left out of the tree and its totals
+    #[default]
+    Skip,
+    // Kept as a single node with its total size but no children
+    Collapse,
+}
+
+// A glob in gitignore syntax, so `node_modules` matches at any depth and
+// `/build` only directly under the scan root
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludeRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub mode: ExcludeMode,
+}
+
+// A file of gitignore-style patterns that all share one mode
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoreFile {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub mode: ExcludeMode,
+}
+
+// Compiled exclusion rules of one scan
+pub struct Exclusions {
+    skip: Gitignore,
+    collapse: Gitignore,
+    cachedir_tag: Option<ExcludeMode>,
+    hide_hidden: bool,
+}
+
+impl Default for Exclusions {
+    fn default() -> Self {
+        Exclusions {
+            skip: Gitignore::empty(),
+            collapse: Gitignore::empty(),
+            cachedir_tag: None,
+            hide_hidden: false,
+        }
+    }
+}
+
+impl Exclusions {
+    pub fn new(root: &Path, options: &ScanOptions) -> Result<Self, ScanError> {
+        let mut skip = GitignoreBuilder::new(root);
+        let mut collapse = GitignoreBuilder::new(root);
+        for rule in &options.exclude {
+            let target = match rule.mode {
+                ExcludeMode::Skip => &mut skip,
+                ExcludeMode::Collapse => &mut collapse,
+            };
+            target.add_line(None, &rule.pattern).map_err(|e| {
+                ScanError::other(root, format!("Invalid exclude pattern '{}': {}", rule.pattern, e))
+            })?;
+        }
+
+        if let Some(ignore_file) = &options.ignore_file {
+            let path = &paths::resolve_entry(&ignore_file.path)?;
+            let text = fs::read_to_string(path).map_err(|e| ScanError::io(path, &e))?;
+            let target = match ignore_file.mode {
+                ExcludeMode::Skip => &mut skip,
+                ExcludeMode::Collapse => &mut collapse,
+            };
+            for line in text.lines() {
+                target.add_line(Some(path.clone()), line).map_err(|e| {
+                    ScanError::other(path, format!("Invalid pattern '{}': {}", line, e))
+                })?;
+            }
+        }
+
+        let build = |builder: GitignoreBuilder| {
+            builder.build().map_err(|e| ScanError::other(root, format!("Invalid exclude rules: {}", e)))
+        };
+        Ok(Exclusions {
+            skip: build(skip)?,
+            collapse: build(collapse)?,
+            cachedir_tag: options.cachedir_tag,
+            hide_hidden: options.hidden == HiddenEntries::Hide,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skip.is_empty() && self.collapse.is_empty() && self.cachedir_tag.is_none() && !self.hide_hidden
+    }
+
+    // Whether a rule leaves entries out of the totals, hidden entries aside
+    pub fn skips_entries(&self) -> bool {
+        !self.skip.is_empty() || self.cachedir_tag == Some(ExcludeMode::Skip)
+    }
+
+    // How `path` is excluded, if at all. Skip rules win over collapse rules.
+    pub fn check(&self, path: &Path, is_dir: bool) -> Option<ExcludeMode> {
+        if self.hide_hidden && is_hidden(path) {
+            return Some(ExcludeMode::Skip);
+        }
+        if self.skip.matched(path, is_dir).is_ignore() {
+            return Some(ExcludeMode::Skip);
+        }
+        if self.collapse.matched(path, is_dir).is_ignore() {
+            return Some(ExcludeMode::Collapse);
+        }
+        self.cachedir_tag.filter(|_| is_dir && has_cachedir_tag(path))
+    }
+}
+
+pub fn is_hidden(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name.as_encoded_bytes().starts_with(b"."))
+}
+
+// A cache directory marks itself with a CACHEDIR.TAG file starting with a
+// fixed signature
+fn has_cachedir_tag(dir: &Path) -> bool {
+    let Ok(mut file) = fs::File::open(dir.join("CACHEDIR.TAG")) else { return false };
+    let mut signature = [0; CACHEDIR_TAG_SIGNATURE.len()];
+    file.read_exact(&mut signature).is_ok() && signature == CACHEDIR_TAG_SIGNATURE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, mode: ExcludeMode) -> ExcludeRule {
+        ExcludeRule { pattern: pattern.to_string(), mode }
+    }
+
+    #[test]
+    fn rules_match_like_gitignore() {
+        let root = Path::new("/scan");
+        let options = ScanOptions {
+            exclude: vec![
+                rule("node_modules", ExcludeMode::Skip),
+                rule("/build", ExcludeMode::Collapse),
+                rule("*.log", ExcludeMode::Collapse),
+                rule("debug.log", ExcludeMode::Skip),
+            ],
+            ..Default::default()
+        };
+        let exclusions = Exclusions::new(root, &options).unwrap();
+        assert_eq!(exclusions.check(Path::new("/scan/a/b/node_modules"), true), Some(ExcludeMode::Skip));
+        assert_eq!(exclusions.check(Path::new("/scan/build"), true), Some(ExcludeMode::Collapse));
+        assert_eq!(exclusions.check(Path::new("/scan/a/build"), true), None);
+        assert_eq!(exclusions.check(Path::new("/scan/a/out.log"), false), Some(ExcludeMode::Collapse));
+        assert_eq!(exclusions.check(Path::new("/scan/debug.log"), false), Some(ExcludeMode::Skip));
+        assert!(exclusions.skips_entries());
+    }
+
+    #[test]
+    fn collapse_rules_leave_totals_alone() {
+        let options = ScanOptions {
+            exclude: vec![rule("target", ExcludeMode::Collapse)],
+            cachedir_tag: Some(ExcludeMode::Collapse),
+            ..Default::default()
+        };
+        let exclusions = Exclusions::new(Path::new("/scan"), &options).unwrap();
+        assert!(!exclusions.is_empty());
+        assert!(!exclusions.skips_entries());
+    }
+
+    #[test]
+    fn dot_entries_are_skipped_when_hidden() {
+        let hide = ScanOptions { hidden: HiddenEntries::Hide, ..Default::default() };
+        let exclusions = Exclusions::new(Path::new("/scan"), &hide).unwrap();
+        assert_eq!(exclusions.check(Path::new("/scan/.git"), true), Some(ExcludeMode::Skip));
+        assert_eq!(exclusions.check(Path::new("/scan/a/.env"), false), Some(ExcludeMode::Skip));
+        assert_eq!(exclusions.check(Path::new("/scan/a.b"), false), None);
+        assert!(!exclusions.skips_entries());
+
+        let dim = ScanOptions { hidden: HiddenEntries::Dim, ..Default::default() };
+        assert!(Exclusions::new(Path::new("/scan"), &dim).unwrap().is_empty());
+    }
+
+    #[test]
+    fn cache_directories_are_told_by_their_tag() {
+        let dir = std::env::temp_dir().join(format!("exclude-cachedir-{}", std::process::id()));
+        fs::create_dir_all(dir.join("tagged")).unwrap();
+        fs::create_dir_all(dir.join("forged")).unwrap();
+        let mut tag = CACHEDIR_TAG_SIGNATURE.to_vec();
+        tag.extend_from_slice(b"\n# a cache\n");
+        fs::write(dir.join("tagged/CACHEDIR.TAG"), tag).unwrap();
+        fs::write(dir.join("forged/CACHEDIR.TAG"), b"Signature: nope").unwrap();
+
+        let options = ScanOptions { cachedir_tag: Some(ExcludeMode::Skip), ..Default::default() };
+        let exclusions = Exclusions::new(&dir, &options).unwrap();
+        let tagged = exclusions.check(&dir.join("tagged"), true);
+        let forged = exclusions.check(&dir.join("forged"), true);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(tagged, Some(ExcludeMode::Skip));
+        assert_eq!(forged, None);
+    }
+}
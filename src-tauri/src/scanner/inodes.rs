@@ -0,0 +1,83 @@
+// Charging files to a scan's totals once per inode, however many paths lead
+// to them
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+
+use super::{dir::Stat, lock, options::SymlinkPolicy, size::ByteSizes, FileNode, ScanState};
+
+pub(super) fn file_node(state: &ScanState, path: &Path, stat: &Stat) -> FileNode {
+    let (sizes, shared) = charge_file(state, stat);
+    let mut node = FileNode::new(
+        path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        path,
+        false,
+        sizes,
+        state.metric(),
+    );
+    node.shared_size = shared.get(state.metric());
+    node
+}
+
+pub(super) fn charge_file(state: &ScanState, stat: &Stat) -> (ByteSizes, ByteSizes) {
+    // Once links are followed any file can be reached twice, not only one
+    // with several hard links
+    let every_file = state.options.symlink_policy != SymlinkPolicy::Never;
+    let (sizes, shared) = charged_sizes(&state.seen_inodes, stat, every_file);
+    state.counters.add_file(sizes.get(state.metric()));
+    (sizes, shared)
+}
+
+// What a file adds to the totals, and how much of that is shared with other
+// hard links. A file already charged adds nothing to either. Only files with
+// several hard links are remembered, unless `every_file` is.
+fn charged_sizes(seen_inodes: &Mutex<HashSet<(u64, u64)>>, stat: &Stat, every_file: bool) -> (ByteSizes, ByteSizes) {
+    let id = if every_file { stat.id } else { stat.hard_link };
+    if id.is_some_and(|id| !lock(seen_inodes).insert(id)) {
+        return (ByteSizes::default(), ByteSizes::default());
+    }
+    let shared = if stat.hard_link.is_some() { stat.sizes } else { ByteSizes::default() };
+    (stat.sizes, shared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn hard_linked_file_is_charged_and_shared_once() {
+        let dir = std::env::temp_dir().join(format!("scanner-hard-links-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let first = dir.join("first");
+        let second = dir.join("second");
+        fs::write(&first, vec![0u8; 4096]).unwrap();
+        fs::hard_link(&first, &second).unwrap();
+
+        let seen_inodes = Mutex::default();
+        let stat_of = |path: &Path| Stat::of(&fs::symlink_metadata(path).unwrap());
+        let (first_sizes, first_shared) = charged_sizes(&seen_inodes, &stat_of(&first), false);
+        let (second_sizes, second_shared) = charged_sizes(&seen_inodes, &stat_of(&second), false);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(first_sizes.apparent, 4096);
+        assert_eq!(first_shared, first_sizes);
+        assert_eq!(second_sizes, ByteSizes::default());
+        assert_eq!(second_shared, ByteSizes::default());
+    }
+
+    #[test]
+    fn single_link_file_is_charged_once_when_every_file_is_remembered() {
+        let file = std::env::temp_dir().join(format!("scanner-single-link-{}", std::process::id()));
+        fs::write(&file, vec![0u8; 4096]).unwrap();
+        let stat = Stat::of(&fs::symlink_metadata(&file).unwrap());
+        fs::remove_file(&file).unwrap();
+
+        let seen_inodes = Mutex::default();
+        assert_eq!(charged_sizes(&seen_inodes, &stat, false).0.apparent, 4096);
+        assert_eq!(charged_sizes(&seen_inodes, &stat, false).0.apparent, 4096);
+        assert_eq!(charged_sizes(&seen_inodes, &stat, true).0.apparent, 4096);
+        assert_eq!(charged_sizes(&seen_inodes, &stat, true).0, ByteSizes::default());
+    }
+}
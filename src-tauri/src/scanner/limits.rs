@@ -0,0 +1,302 @@
+// Trimming a tree to the scan's limits, and the aggregate nodes standing in
+// for entries a tree leaves out or bytes it lists no entry for
+
+use std::path::Path;
+
+use super::{
+    dir::{self, NameBuf},
+    options::TreeLimits,
+    size::{ByteSizes, SizeMetric},
+    FileNode,
+};
+
+impl FileNode {
+    // Trims the tree to the reported depth, entry size and number of
+    // children per directory, keeping the largest entries
+    pub(super) fn apply_limits(&mut self, limits: &TreeLimits) {
+        self.apply_limits_at(limits, 0);
+    }
+
+    fn apply_limits_at(&mut self, limits: &TreeLimits, level: u32) {
+        let Some(children) = self.children.as_mut() else { return };
+        let below_depth = limits.depth.is_some_and(|depth| level >= depth);
+        // An aggregate the walker or a tool already made takes in whatever
+        // else is left out, so limiting a tree twice changes nothing
+        let aggregate = children
+            .iter()
+            .position(|child| child.other_count > 0)
+            .map(|index| children.remove(index));
+        // A directory's own blocks are no entry to leave out
+        let own_blocks = children.iter().position(|child| child.own_blocks).map(|index| children.remove(index));
+        children.sort_by_key(|child| std::cmp::Reverse(child.size));
+        let kept = if below_depth {
+            0
+        } else {
+            children
+                .partition_point(|child| child.size >= limits.min_size)
+                .min(limits.max_children.unwrap_or(usize::MAX))
+        };
+        let (flagged, mut dropped): (Vec<FileNode>, Vec<FileNode>) =
+            children.split_off(kept).into_iter().partition(FileNode::carries_flags);
+        if below_depth && flagged.is_empty() {
+            self.children = None;
+            return;
+        }
+        children.extend(flagged);
+        for child in children.iter_mut() {
+            child.apply_limits_at(limits, level + 1);
+        }
+        // What was left out stays in the layout as one node, so the children
+        // still add up to this directory's size
+        dropped.extend(aggregate);
+        match dropped.as_mut_slice() {
+            [] => {}
+            [only] if only.other_count > 0 => children.push(std::mem::take(only)),
+            _ => children.push(FileNode::other(&dropped)),
+        }
+        children.extend(own_blocks);
+        if children.is_empty() {
+            self.children = None;
+        }
+    }
+
+    // Unreadable entries and mount points are never folded away or cut off,
+    // and neither is anything leading to one, so the tree still shows where
+    // they are
+    fn carries_flags(&self) -> bool {
+        self.inaccessible || self.fs_type.is_some() || self.children.iter().flatten().any(FileNode::carries_flags)
+    }
+
+    // Lists the blocks of a directory's own entry beside the children it
+    // reports, if it reports any
+    pub(super) fn add_own_blocks(&mut self, sizes: ByteSizes, metric: SizeMetric) {
+        let Some(children) = self.children.as_mut() else { return };
+        if sizes != ByteSizes::default() {
+            let mut node = FileNode { name: "Directory entry".to_string(), own_blocks: true, ..Default::default() };
+            node.set_sizes(sizes, metric);
+            children.push(node);
+        }
+    }
+
+    // Bytes of a directory a preview cannot place yet
+    pub(super) fn unattributed(sizes: ByteSizes, metric: SizeMetric) -> Self {
+        let mut node = FileNode {
+            name: "Unattributed small files".to_string(),
+            unattributed: true,
+            ..Default::default()
+        };
+        node.set_sizes(sizes, metric);
+        node
+    }
+
+    // Aggregate of children left out of a directory
+    fn other(dropped: &[FileNode]) -> Self {
+        FileNode {
+            size: dropped.iter().map(|node| node.size).sum(),
+            apparent_size: dropped.iter().map(|node| node.apparent_size).sum(),
+            allocated_size: dropped.iter().map(|node| node.allocated_size).sum(),
+            shared_size: dropped.iter().map(|node| node.shared_size).sum(),
+            ..FileNode::other_items(dropped.iter().map(|node| node.other_count.max(1)).sum())
+        }
+    }
+
+    // Aggregate of `count` entries, for the caller to give sizes
+    pub(super) fn other_items(count: u64) -> Self {
+        FileNode {
+            name: format!("Other ({} smaller {})", count, if count == 1 { "item" } else { "items" }),
+            other_count: count,
+            ..Default::default()
+        }
+    }
+
+    // Aggregate for bytes a tool counted in a directory without listing the
+    // entries they belong to. The tool does not say how many there are, only
+    // that there is at least one.
+    pub(super) fn untracked(sizes: ByteSizes, metric: SizeMetric) -> Self {
+        let mut node = FileNode {
+            name: "Other (smaller items)".to_string(),
+            other_count: 1,
+            ..Default::default()
+        };
+        node.set_sizes(sizes, metric);
+        node
+    }
+}
+
+// The nodes a directory reports under the tree limits, largest first, with
+// the entries left out folded into one aggregate and flagged ones kept as for
+// apply_limits. Files only get a path and a
+// node here, once they are known to be kept.
+pub(super) fn limited_children(
+    limits: &TreeLimits,
+    path: &Path,
+    level: u32,
+    mut children: Vec<Child>,
+    metric: SizeMetric,
+) -> Option<Vec<FileNode>> {
+    if children.is_empty() {
+        return None;
+    }
+    let below_depth = limits.depth.is_some_and(|depth| level >= depth);
+    children.sort_by_key(|child| std::cmp::Reverse(child.sizes().get(metric)));
+    let kept = if below_depth {
+        0
+    } else {
+        children
+            .partition_point(|child| child.sizes().get(metric) >= limits.min_size)
+            .min(limits.max_children.unwrap_or(usize::MAX))
+    };
+    let (flagged, dropped): (Vec<Child>, Vec<Child>) =
+        children.split_off(kept).into_iter().partition(Child::carries_flags);
+    if below_depth && flagged.is_empty() {
+        return None;
+    }
+    children.extend(flagged);
+    let mut nodes: Vec<FileNode> = children.into_iter().map(|child| child.into_node(path, metric)).collect();
+    if !dropped.is_empty() {
+        let mut other = FileNode::other_items(dropped.len() as u64);
+        other.set_sizes(dropped.iter().map(Child::sizes).sum(), metric);
+        other.shared_size = dropped.iter().map(Child::shared_size).sum();
+        nodes.push(other);
+    }
+    Some(nodes)
+}
+
+// An entry of a directory being walked. Plain files stay a name and sizes
+// until the directory's limits show whether they get a node of their own.
+pub(super) enum Child {
+    Node(FileNode),
+    File { name: NameBuf, sizes: ByteSizes, shared_size: u64 },
+}
+
+impl Child {
+    pub(super) fn sizes(&self) -> ByteSizes {
+        match self {
+            Child::Node(node) => node.sizes(),
+            Child::File { sizes, .. } => *sizes,
+        }
+    }
+
+    fn carries_flags(&self) -> bool {
+        matches!(self, Child::Node(node) if node.carries_flags())
+    }
+
+    pub(super) fn shared_size(&self) -> u64 {
+        match self {
+            Child::Node(node) => node.shared_size,
+            Child::File { shared_size, .. } => *shared_size,
+        }
+    }
+
+    fn into_node(self, dir: &Path, metric: SizeMetric) -> FileNode {
+        match self {
+            Child::Node(node) => node,
+            Child::File { name, sizes, shared_size } => {
+                let name = dir::name_os_str(&name);
+                let mut node = FileNode::new(name.to_string_lossy().to_string(), &dir.join(name), false, sizes, metric);
+                node.shared_size = shared_size;
+                node
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(name: &str, size: u64) -> FileNode {
+        FileNode { name: name.to_string(), size, apparent_size: size, allocated_size: size, ..Default::default() }
+    }
+
+    fn dir(name: &str, children: Vec<FileNode>) -> FileNode {
+        let size = children.iter().map(|child| child.size).sum();
+        FileNode { is_dir: true, children: Some(children), ..leaf(name, size) }
+    }
+
+    fn names(node: &FileNode) -> Vec<&str> {
+        node.children.iter().flatten().map(|child| child.name.as_str()).collect()
+    }
+
+    fn limits(depth: Option<u32>, max_children: Option<usize>, min_size: u64) -> TreeLimits {
+        TreeLimits { depth, max_children, min_size }
+    }
+
+    #[test]
+    fn depth_drops_children_below_the_limit() {
+        let mut root = dir("root", vec![dir("a", vec![dir("b", vec![leaf("f", 5)])])]);
+        root.apply_limits(&limits(Some(2), None, 0));
+        let a = &root.children.as_ref().unwrap()[0];
+        let b = &a.children.as_ref().unwrap()[0];
+        assert!(b.children.is_none());
+        assert_eq!(b.size, 5);
+    }
+
+    #[test]
+    fn children_past_the_limits_are_folded_into_other() {
+        let mut root = dir("root", vec![leaf("small", 1), leaf("big", 100), leaf("mid", 50), leaf("tiny", 2)]);
+        root.apply_limits(&limits(None, Some(2), 0));
+        assert_eq!(names(&root), ["big", "mid", "Other (2 smaller items)"]);
+        let other = &root.children.as_ref().unwrap()[2];
+        assert_eq!((other.size, other.other_count), (3, 2));
+
+        let mut root = dir("root", vec![leaf("small", 1), leaf("big", 100)]);
+        root.apply_limits(&limits(None, None, 10));
+        assert_eq!(names(&root), ["big", "Other (1 smaller item)"]);
+    }
+
+    #[test]
+    fn limiting_twice_keeps_one_aggregate() {
+        let children = (1..=10).map(|i| leaf(&format!("f{}", i), i)).collect();
+        let mut root = dir("root", children);
+        root.apply_limits(&limits(None, Some(3), 0));
+        let once = names(&root).join(",");
+        root.apply_limits(&limits(None, Some(3), 0));
+        assert_eq!(names(&root).join(","), once);
+
+        // A tighter limit adds to the existing aggregate instead of nesting it
+        root.apply_limits(&limits(None, Some(1), 0));
+        assert_eq!(names(&root), ["f10", "Other (9 smaller items)"]);
+        assert_eq!(root.children.as_ref().unwrap()[1].size, 45);
+    }
+
+    #[test]
+    fn flagged_entries_are_not_folded_into_other() {
+        let unreadable = FileNode { inaccessible: true, ..leaf("unreadable", 0) };
+        let mount = FileNode { fs_type: Some("nfs".to_string()), ..leaf("mount", 0) };
+        let mut root = dir("root", vec![leaf("big", 100), leaf("small", 1), unreadable, mount]);
+        root.apply_limits(&limits(None, Some(1), 10));
+        assert_eq!(names(&root), ["big", "unreadable", "mount", "Other (1 smaller item)"]);
+    }
+
+    #[test]
+    fn depth_cut_keeps_the_way_to_flagged_entries() {
+        let mount = FileNode { fs_type: Some("proc".to_string()), ..leaf("proc", 0) };
+        let mut root = dir(
+            "root",
+            vec![dir("a", vec![dir("b", vec![mount, leaf("f", 4)]), leaf("g", 7)]), dir("c", vec![leaf("h", 2)])],
+        );
+        root.apply_limits(&limits(Some(1), None, 0));
+        assert_eq!(names(&root), ["a", "c"]);
+        let a = &root.children.as_ref().unwrap()[0];
+        assert_eq!(names(a), ["b", "Other (1 smaller item)"]);
+        assert_eq!(names(&a.children.as_ref().unwrap()[0]), ["proc", "Other (1 smaller item)"]);
+        assert!(root.children.as_ref().unwrap()[1].children.is_none());
+    }
+
+    #[test]
+    fn own_blocks_are_not_folded_into_other() {
+        let mut root = dir("root", vec![leaf("a", 10), leaf("b", 5)]);
+        root.add_own_blocks(ByteSizes::new(40, 40), SizeMetric::Apparent);
+        root.apply_limits(&limits(None, Some(1), 0));
+        assert_eq!(names(&root), ["a", "Other (1 smaller item)", "Directory entry"]);
+    }
+
+    #[test]
+    fn untracked_bytes_stay_as_they_are_when_nothing_else_is_dropped() {
+        let untracked = FileNode::untracked(ByteSizes::new(3, 3), SizeMetric::Apparent);
+        let mut root = dir("root", vec![leaf("a", 10), untracked]);
+        root.apply_limits(&limits(None, Some(5), 0));
+        assert_eq!(names(&root), ["a", "Other (smaller items)"]);
+    }
+}
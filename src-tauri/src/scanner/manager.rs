@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    backend::BackendKind,
+    error::ScanError,
+    process::ChildProcesses,
+    progress::{ScanCounters, ScanTotals},
+};
+
+// Finished scans kept around so their status can still be queried
+const MAX_FINISHED_SCANS: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanRunState {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanStatus {
+    pub id: String,
+    pub path: String,
+    pub backend: BackendKind,
+    pub state: ScanRunState,
+    #[serde(flatten)]
+    pub totals: ScanTotals,
+    pub elapsed_ms: u64,
+    pub error: Option<ScanError>,
+}
+
+// Handles a running scan shares with the manager
+pub struct ScanRegistration {
+    pub id: String,
+    pub is_cancelled: Arc<AtomicBool>,
+    pub counters: Arc<ScanCounters>,
+    pub children: Arc<ChildProcesses>,
+}
+
+struct ScanEntry {
+    path: String,
+    backend: BackendKind,
+    is_cancelled: Arc<AtomicBool>,
+    counters: Arc<ScanCounters>,
+    children: Arc<ChildProcesses>,
+    started: Instant,
+    finished: Option<Duration>,
+    state: ScanRunState,
+    error: Option<ScanError>,
+}
+
+impl ScanEntry {
+    fn status(&self, id: &str) -> ScanStatus {
+        ScanStatus {
+            id: id.to_string(),
+            path: self.path.clone(),
+            backend: self.backend,
+            state: self.state,
+            totals: self.counters.snapshot(),
+            elapsed_ms: self.finished.unwrap_or_else(|| self.started.elapsed()).as_millis() as u64,
+            error: self.error.clone(),
+        }
+    }
+}
+
+// Tracks every scan by ID so they can run side by side and be addressed
+// individually. Registered as Tauri managed state.
+#[derive(Default)]
+pub struct ScanManager {
+    scans: Mutex<HashMap<String, ScanEntry>>,
+    next_id: AtomicU64,
+}
+
+impl ScanManager {
+    // Registers a new scan, using the caller's ID when one is supplied
+    pub fn begin(
+        &self,
+        requested_id: Option<String>,
+        path: &Path,
+        backend: BackendKind,
+    ) -> Result<ScanRegistration, ScanError> {
+        let mut scans =
+            self.scans.lock().map_err(|_| ScanError::other(path, "Scan registry is unavailable"))?;
+
+        let id = requested_id.unwrap_or_else(|| {
+            format!("scan-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+        });
+        if scans.get(&id).is_some_and(|entry| entry.state == ScanRunState::Running) {
+            return Err(ScanError::AlreadyRunning {
+                path: path.to_string_lossy().to_string(),
+                code: None,
+                scan_id: id,
+            });
+        }
+
+        prune_finished(&mut scans);
+
+        let registration = ScanRegistration {
+            id: id.clone(),
+            is_cancelled: Arc::new(AtomicBool::new(false)),
+            counters: Arc::new(ScanCounters::default()),
+            children: Arc::new(ChildProcesses::default()),
+        };
+        scans.insert(
+            id,
+            ScanEntry {
+                path: path.to_string_lossy().to_string(),
+                backend,
+                is_cancelled: registration.is_cancelled.clone(),
+                counters: registration.counters.clone(),
+                children: registration.children.clone(),
+                started: Instant::now(),
+                finished: None,
+                state: ScanRunState::Running,
+                error: None,
+            },
+        );
+
+        println!("[MANAGER] Started scan {} on {}", registration.id, path.display());
+        Ok(registration)
+    }
+
+    // Records how a scan ended
+    pub fn finish<T>(&self, id: &str, result: &Result<T, ScanError>) {
+        let Ok(mut scans) = self.scans.lock() else { return };
+        let Some(entry) = scans.get_mut(id) else { return };
+
+        entry.finished = Some(entry.started.elapsed());
+        entry.state = match result {
+            // Cancelled scans still return a partial tree
+            _ if entry.is_cancelled.load(Ordering::Relaxed) => ScanRunState::Cancelled,
+            Ok(_) => ScanRunState::Completed,
+            Err(_) => ScanRunState::Failed,
+        };
+        entry.error = result.as_ref().err().cloned();
+        println!("[MANAGER] Scan {} finished: {:?}", id, entry.state);
+    }
+
+    // Flags one scan for cancellation and kills its external tools right away;
+    // returns false for unknown IDs
+    pub fn cancel(&self, id: &str) -> bool {
+        let Ok(scans) = self.scans.lock() else { return false };
+        match scans.get(id) {
+            Some(entry) => {
+                entry.is_cancelled.store(true, Ordering::Relaxed);
+                entry.children.kill_all();
+                println!("[CANCEL] Scan {} cancellation requested", id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn status(&self, id: &str) -> Option<ScanStatus> {
+        let scans = self.scans.lock().ok()?;
+        scans.get(id).map(|entry| entry.status(id))
+    }
+
+    pub fn list(&self) -> Vec<ScanStatus> {
+        let Ok(scans) = self.scans.lock() else { return Vec::new() };
+        // Oldest first; IDs can come from the caller, so they say nothing
+        // about order
+        let mut entries: Vec<(&String, &ScanEntry)> = scans.iter().collect();
+        entries.sort_by_key(|(id, entry)| (entry.started, *id));
+        entries.into_iter().map(|(id, entry)| entry.status(id)).collect()
+    }
+}
+
+// Drops the oldest finished scans beyond MAX_FINISHED_SCANS
+fn prune_finished(scans: &mut HashMap<String, ScanEntry>) {
+    let mut finished: Vec<(String, Instant)> = scans
+        .iter()
+        .filter(|(_, entry)| entry.state != ScanRunState::Running)
+        .map(|(id, entry)| (id.clone(), entry.started))
+        .collect();
+    if finished.len() < MAX_FINISHED_SCANS {
+        return;
+    }
+
+    finished.sort_by_key(|(_, started)| *started);
+    for (id, _) in finished.iter().take(finished.len() + 1 - MAX_FINISHED_SCANS) {
+        scans.remove(id);
+    }
+}
+
+// List every known scan, running or recently finished
+#[tauri::command]
+pub fn list_scans(manager: tauri::State<'_, ScanManager>) -> Vec<ScanStatus> {
+    manager.list()
+}
+
+// Get the status of a single scan
+#[tauri::command]
+pub fn get_scan_status(
+    id: String,
+    manager: tauri::State<'_, ScanManager>,
+) -> Result<ScanStatus, ScanError> {
+    manager.status(&id).ok_or_else(|| ScanError::unknown_scan(&id))
+}
+
+// Cancel a single scan by ID
+#[tauri::command]
+pub fn cancel_scan(id: String, manager: tauri::State<'_, ScanManager>) -> Result<(), ScanError> {
+    if manager.cancel(&id) {
+        Ok(())
+    } else {
+        Err(ScanError::unknown_scan(&id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_is_in_start_order() {
+        let manager = ScanManager::default();
+        for _ in 0..11 {
+            manager.begin(None, Path::new("/"), BackendKind::Native).unwrap();
+        }
+        let ids: Vec<String> = manager.list().into_iter().map(|status| status.id).collect();
+        let expected: Vec<String> = (1..=11).map(|n| format!("scan-{}", n)).collect();
+        assert_eq!(ids, expected);
+    }
+
+    #[test]
+    fn running_id_cannot_be_reused_until_finished() {
+        let manager = ScanManager::default();
+        let id = Some("mine".to_string());
+        manager.begin(id.clone(), Path::new("/"), BackendKind::Native).unwrap();
+        assert!(matches!(
+            manager.begin(id.clone(), Path::new("/"), BackendKind::Native),
+            Err(ScanError::AlreadyRunning { .. })
+        ));
+
+        manager.finish::<()>("mine", &Ok(()));
+        assert_eq!(manager.status("mine").unwrap().state, ScanRunState::Completed);
+        assert!(manager.begin(id, Path::new("/"), BackendKind::Native).is_ok());
+    }
+
+    #[test]
+    fn cancelled_scans_finish_as_cancelled() {
+        let manager = ScanManager::default();
+        let registration = manager.begin(None, Path::new("/"), BackendKind::Native).unwrap();
+        assert!(manager.cancel(&registration.id));
+        assert!(registration.is_cancelled.load(Ordering::Relaxed));
+        assert!(!manager.cancel("missing"));
+
+        manager.finish::<()>(&registration.id, &Ok(()));
+        assert_eq!(manager.status(&registration.id).unwrap().state, ScanRunState::Cancelled);
+    }
+
+    #[test]
+    fn oldest_finished_scans_are_pruned() {
+        let manager = ScanManager::default();
+        for _ in 0..MAX_FINISHED_SCANS + 5 {
+            let registration = manager.begin(None, Path::new("/"), BackendKind::Native).unwrap();
+            manager.finish::<()>(&registration.id, &Ok(()));
+        }
+        let ids: Vec<String> = manager.list().into_iter().map(|status| status.id).collect();
+        assert_eq!(ids.len(), MAX_FINISHED_SCANS);
+        assert_eq!(ids.last().unwrap(), &format!("scan-{}", MAX_FINISHED_SCANS + 5));
+        assert!(manager.status("scan-1").is_none());
+    }
+}
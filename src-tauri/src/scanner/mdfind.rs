@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{
+    backend::{find_in_path, BackendCapabilities, BackendKind, ScanBackend},
+    cache::{add_files_to_cache, get_cached_files_for_directory},
+    error::ScanError,
+    native::scan_directory_native,
+    process::{nul_records, path_from_bytes, run_command},
+    progress::ScanPhase,
+    size::ByteSizes,
+    tree::build_tree,
+    Backfill, FileNode, ScanState,
+};
+
+// Results read from each mdfind pass, and how many of the largest are kept
+const MDFIND_CANDIDATES: usize = 2000;
+const MDFIND_KEEP: usize = 1000;
+
+pub struct MdfindBackend;
+
+impl ScanBackend for MdfindBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Mdfind
+    }
+
+    fn name(&self) -> &'static str {
+        "mdfind (Spotlight)"
+    }
+
+    // Spotlight only exists on macOS, and can be missing from minimal PATHs
+    fn is_available(&self, _app_handle: &tauri::AppHandle) -> bool {
+        cfg!(target_os = "macos") && find_in_path("mdfind").is_some()
+    }
+
+    // Only files above 5MB are found, so on its own the tree is a large-file
+    // sample; the backfill a scan can ask for makes it complete
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            full_depth: false,
+            apparent_size: true,
+            allocated_size: true,
+            streaming: true,
+        }
+    }
+
+    fn scan(
+        &self,
+        path: &Path,
+        app_handle: &tauri::AppHandle,
+        state: &mut ScanState,
+    ) -> Result<FileNode, ScanError> {
+        scan_directory_with_mdfind(path, app_handle, state)
+    }
+}
+
+// Multi-pass mdfind scanning for macOS
+pub fn scan_directory_with_mdfind(
+    path: &Path,
+    app_handle: &tauri::AppHandle,
+    state: &mut ScanState,
+) -> Result<FileNode, ScanError> {
+    let path_str = path.to_string_lossy();
+    println!("[MDFIND] Starting multi-pass mdfind scan on: {}", path_str);
+    
+    // Multiple passes with decreasing size thresholds
+    // Pass 1: > 100MB, Pass 2: > 50MB, Pass 3: > 10MB, Pass 4: > 5MB
+    let size_thresholds = [
+        (104857600u64, "100MB"),  // 100MB
+        (52428800u64, "50MB"),    // 50MB  
+        (10485760u64, "10MB"),    // 10MB
+        (5242880u64, "5MB"),      // 5MB
+    ];
+    
+    let mut all_files: HashMap<PathBuf, ByteSizes> = HashMap::new();
+    let metric = state.metric();
+    let backfill = state.options.backfill;
+    let total_passes = size_thresholds.len() as u32 + u32::from(backfill);
+    
+    // Get cached files for this directory to emit as intermediate results
+    let cached_files = get_cached_files_for_directory(path);
+    if !cached_files.is_empty() {
+        println!("[MDFIND] Found {} cached files - emitting as initial preview", cached_files.len());
+        
+        // Emit cached results as intermediate preview only - don't seed the actual scan
+        match state.emit_intermediate(app_handle, build_tree(&cached_files, path, metric)) {
+            Ok(_) => println!("[MDFIND] Emitted cached preview ({} files) - starting fresh mdfind scan", cached_files.len()),
+            Err(e) => println!("[MDFIND] Failed to emit cached preview: {:?}", e),
+        }
+    }
+    
+    // Always start fresh mdfind scan regardless of cache
+    
+    for (pass_idx, (threshold, threshold_name)) in size_thresholds.iter().enumerate() {
+        // Check for cancellation or timeout
+        if let Err(e) = state.check_interrupted() {
+            println!("[MDFIND] Scan stopped during pass {}: {}", pass_idx + 1, e);
+            return Err(e);
+        }
+        
+        println!("[MDFIND] Pass {}/{}: Finding files larger than {}",
+            pass_idx + 1, size_thresholds.len(), threshold_name);
+        state.set_pass(pass_idx as u32 + 1, total_passes);
+        state.emit(app_handle, &path_str);
+        
+        // Run mdfind with an argument vector and NUL-separated results, then
+        // stat the candidates in-process
+        let query = format!("kMDItemFSSize > {}", threshold);
+        let output = run_command(Command::new("mdfind").arg("-0").arg("-onlyin").arg(path).arg(&query), state)?;
+        let found_files = largest_files(&output.stdout, state);
+        
+        let files_before_pass = all_files.len();
+        println!("[MDFIND] Pass {} found {} files from mdfind", pass_idx + 1, found_files.len());
+        
+        for (file_path_buf, sizes) in found_files {
+            // Only process files within our target directory, skipping any we
+            // already have from a previous pass
+            if !file_path_buf.starts_with(path) || all_files.contains_key(&file_path_buf) {
+                continue;
+            }
+            
+            state.counters.add_file(sizes.get(metric));
+            state.record_file(&file_path_buf, sizes);
+            all_files.insert(file_path_buf, sizes);
+        }
+        
+        // Update progress
+        let files_after_pass = all_files.len();
+        let new_files_added = files_after_pass - files_before_pass;
+        state.emit(app_handle, &path_str);
+        
+        println!("[MDFIND] Pass {} added {} new files (total: {})", 
+            pass_idx + 1, new_files_added, files_after_pass);
+        
+        // Emit intermediate results after each pass if we have files
+        if all_files.len() > 10 {
+            println!("[MDFIND] Emitting intermediate results with {} files", all_files.len());
+            // Build and emit intermediate tree
+            match state.emit_intermediate(app_handle, build_tree(&all_files, path, metric)) {
+                Ok(_) => println!("[MDFIND] Successfully emitted intermediate results"),
+                Err(e) => println!("[MDFIND] Failed to emit intermediate results: {:?}", e),
+            }
+        }
+    }
+    
+    // Emit final status update
+    state.set_phase(ScanPhase::BuildingTree);
+    state.emit(app_handle, &path_str);
+    
+    // Update cache with discovered files
+    add_files_to_cache(&all_files);
+    
+    // Build final tree structure from collected files
+    let mut root = build_tree(&all_files, path, metric);
+    state.prune_excluded(&mut root);
+    if !backfill {
+        return Ok(root);
+    }
+    
+    // The preview only covers large files, so a native walk follows to find
+    // where the rest of the space goes. Until it has finished a directory,
+    // that directory keeps its preview size.
+    println!("[MDFIND] Backfilling exact totals with a native walk");
+    if let Err(e) = state.emit_intermediate(app_handle, root.clone()) {
+        println!("[MDFIND] Failed to emit preview: {:?}", e);
+    }
+    state.set_phase(ScanPhase::Scanning);
+    state.set_pass(total_passes, total_passes);
+    state.counters.reset_progress();
+    state.backfill = Some(Backfill::new(root, metric));
+    let result = scan_directory_native(path, app_handle, state);
+    state.backfill = None;
+    result
+}
+
+// Stats the first MDFIND_CANDIDATES results and keeps the MDFIND_KEEP largest.
+// The index can be stale, so results that no longer stat are reported as
+// skipped.
+fn largest_files(output: &[u8], state: &mut ScanState) -> Vec<(PathBuf, ByteSizes)> {
+    let metric = state.metric();
+    let mut files: Vec<(PathBuf, ByteSizes)> = nul_records(output)
+        .take(MDFIND_CANDIDATES)
+        .map(path_from_bytes)
+        .filter_map(|file_path| match fs::symlink_metadata(&file_path) {
+            Ok(metadata) => metadata.is_file().then(|| (file_path, ByteSizes::of(&metadata))),
+            Err(e) => {
+                state.skip(&file_path, false, &e);
+                None
+            }
+        })
+        .collect();
+    files.sort_by_key(|(_, sizes)| std::cmp::Reverse(sizes.get(metric)));
+    files.truncate(MDFIND_KEEP);
+    files
+}
@@ -0,0 +1,256 @@
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{lock, FileNode, ScanState};
+
+// Filesystem types that expose kernel or runtime state rather than stored data
+const PSEUDO_FS_TYPES: &[&str] = &[
+    "autofs", "binfmt_misc", "bpf", "cgroup", "cgroup2", "configfs", "debugfs", "devpts",
+    "devtmpfs", "efivarfs", "fusectl", "hugetlbfs", "mqueue", "nsfs", "proc", "pstore",
+    "ramfs", "rpc_pipefs", "securityfs", "selinuxfs", "sysfs", "tmpfs", "tracefs",
+];
+
+// Filesystem types that go over the network or through a userspace daemon,
+// either of which can stall a walk indefinitely
+const NETWORK_FS_TYPES: &[&str] = &[
+    "9p", "afs", "ceph", "cifs", "davfs", "fuse", "glusterfs", "lustre", "ncpfs", "nfs", "nfs4",
+    "smb3", "smbfs", "sshfs",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MountKind {
+    Real,
+    Pseudo,
+    // Network and FUSE filesystems
+    Network,
+}
+
+impl MountKind {
+    pub fn of(fs_type: &str) -> Self {
+        // FUSE mounts report their type as fuse.<daemon>
+        let base = fs_type.split('.').next().unwrap_or(fs_type);
+        if PSEUDO_FS_TYPES.contains(&fs_type) {
+            MountKind::Pseudo
+        } else if NETWORK_FS_TYPES.contains(&base) {
+            MountKind::Network
+        } else {
+            MountKind::Real
+        }
+    }
+}
+
+// One entry of the system mount table
+#[derive(Debug, Clone)]
+pub struct Mount {
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    pub source: String,
+    pub kind: MountKind,
+}
+
+// A mount a scan left out because of its kind, reported with the result
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcludedMount {
+    pub path: String,
+    pub fs_type: String,
+    pub source: String,
+    pub kind: MountKind,
+}
+
+impl From<&Mount> for ExcludedMount {
+    fn from(mount: &Mount) -> Self {
+        ExcludedMount {
+            path: mount.mount_point.to_string_lossy().to_string(),
+            fs_type: mount.fs_type.clone(),
+            source: mount.source.clone(),
+            kind: mount.kind,
+        }
+    }
+}
+
+// Reads the mount table, latest mounts last so they win over anything they
+// were mounted on top of
+#[cfg(target_os = "linux")]
+pub fn read_mounts() -> Vec<Mount> {
+    match std::fs::read_to_string("/proc/self/mountinfo") {
+        Ok(text) => text.lines().filter_map(parse_mountinfo_line).collect(),
+        Err(e) => {
+            println!("[MOUNTS] Could not read /proc/self/mountinfo: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// Other platforms have no mountinfo; boundaries are still found by device ID
+// but their mounts go unlabeled
+#[cfg(not(target_os = "linux"))]
+pub fn read_mounts() -> Vec<Mount> {
+    Vec::new()
+}
+
+// Parses one line of /proc/self/mountinfo:
+// `id parent major:minor root mount-point options [optional...] - type source super-options`
+#[cfg(target_os = "linux")]
+fn parse_mountinfo_line(line: &str) -> Option<Mount> {
+    let (before, after) = line.split_once(" - ")?;
+    let mount_point = before.split(' ').nth(4)?;
+    let mut after = after.split(' ');
+    let fs_type = after.next()?;
+    let source = after.next().unwrap_or_default();
+    Some(Mount {
+        mount_point: super::process::path_from_bytes(&unescape_octal(mount_point)),
+        fs_type: fs_type.to_string(),
+        source: String::from_utf8_lossy(&unescape_octal(source)).to_string(),
+        kind: MountKind::of(fs_type),
+    })
+}
+
+// mountinfo escapes space, tab, newline and backslash as \ooo
+#[cfg(target_os = "linux")]
+fn unescape_octal(field: &str) -> Vec<u8> {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes.get(i + 1..i + 4).filter(|_| bytes[i] == b'\\').and_then(|digits| {
+            std::str::from_utf8(digits).ok().and_then(|digits| u8::from_str_radix(digits, 8).ok())
+        });
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+// The mount whose mount point is exactly `path`
+pub fn mount_at<'a>(mounts: &'a [Mount], path: &Path) -> Option<&'a Mount> {
+    mounts.iter().rev().find(|mount| mount.mount_point == path)
+}
+
+#[cfg(unix)]
+pub fn device_id(metadata: &Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+pub fn device_id(_metadata: &Metadata) -> Option<u64> {
+    None
+}
+
+// How a scan treats the mounts it meets
+impl ScanState {
+    // In one-filesystem mode, a leaf for a directory that lives on another
+    // device than the scan root
+    pub(super) fn mount_point_node(&self, path: &Path, device: Option<u64>) -> Option<FileNode> {
+        let root_device = self.root_device?;
+        if device == Some(root_device) {
+            return None;
+        }
+        Some(FileNode::mount_point(path, mount_at(&self.mounts, path)))
+    }
+
+    // Pseudo filesystems are left out unless asked for and network ones are
+    // opt-in
+    fn is_excluded(&self, mount: &Mount) -> bool {
+        match mount.kind {
+            MountKind::Real => false,
+            MountKind::Pseudo => !self.options.include_pseudo_filesystems,
+            MountKind::Network => !self.options.include_network_filesystems,
+        }
+    }
+
+    pub(super) fn set_mounts(&mut self, mounts: Vec<Mount>) {
+        self.mounts = mounts;
+        self.excluded_mount_points = self
+            .mounts
+            .iter()
+            .map(|mount| &mount.mount_point)
+            .filter(|point| mount_at(&self.mounts, point).is_some_and(|mount| self.is_excluded(mount)))
+            .cloned()
+            .collect();
+    }
+
+    pub(super) fn is_excluded_mount(&self, path: &Path) -> bool {
+        self.excluded_mount_points.contains(path)
+    }
+
+    // A leaf for `path` if an excluded kind of filesystem is mounted there.
+    // Decided from the mount table alone, so a stale network mount is never
+    // touched.
+    pub(super) fn excluded_mount_node(&self, path: &Path) -> Option<FileNode> {
+        let mount = mount_at(&self.mounts, path).filter(|mount| self.is_excluded(mount))?;
+        let mut excluded_mounts = lock(&self.excluded_mounts);
+        if !excluded_mounts.iter().any(|excluded| Path::new(&excluded.path) == path) {
+            println!("[SCAN] Excluding {} mount at {}", mount.fs_type, path.display());
+            excluded_mounts.push(mount.into());
+        }
+        Some(FileNode::mount_point(path, Some(mount)))
+    }
+
+    // Mount points below `dir` that external tools must be told to skip
+    pub(super) fn excluded_mounts_below(&self, dir: &Path) -> Vec<PathBuf> {
+        self.mounts
+            .iter()
+            .filter(|mount| mount.mount_point != dir && mount.mount_point.starts_with(dir))
+            .filter(|mount| self.is_excluded(mount))
+            .map(|mount| mount.mount_point.clone())
+            .collect()
+    }
+
+    // Leaves for the mount points directly inside `dir` that the scan does
+    // not enter, for tools that leave them out of their output entirely
+    pub(super) fn mount_points_in(&self, dir: &Path) -> Vec<FileNode> {
+        let mut points: Vec<PathBuf> = self
+            .mounts
+            .iter()
+            .map(|mount| mount.mount_point.clone())
+            .filter(|point| point.parent() == Some(dir))
+            .filter(|point| self.root_device.is_some() || self.is_excluded_mount(point))
+            .collect();
+        points.sort();
+        points.dedup();
+        points
+            .iter()
+            .map(|point| {
+                self.excluded_mount_node(point)
+                    .unwrap_or_else(|| FileNode::mount_point(point, mount_at(&self.mounts, point)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mountinfo_line_with_escaped_mount_point() {
+        let line = r"36 35 98:0 /mnt1 /mnt/parent\040dir rw,noatime master:1 - ext3 /dev/root rw,errors=continue";
+        let mount = parse_mountinfo_line(line).unwrap();
+        assert_eq!(mount.mount_point, PathBuf::from("/mnt/parent dir"));
+        assert_eq!(mount.fs_type, "ext3");
+        assert_eq!(mount.source, "/dev/root");
+        assert_eq!(mount.kind, MountKind::Real);
+    }
+
+    #[test]
+    fn mountinfo_line_kinds() {
+        let proc = parse_mountinfo_line("22 1 0:21 / /proc rw,nosuid shared:12 - proc proc rw").unwrap();
+        assert_eq!(proc.kind, MountKind::Pseudo);
+        let sshfs = parse_mountinfo_line("90 22 0:50 / /home/me/remote rw - fuse.sshfs me@host: rw").unwrap();
+        assert_eq!(sshfs.kind, MountKind::Network);
+        assert!(parse_mountinfo_line("not a mountinfo line").is_none());
+    }
+}
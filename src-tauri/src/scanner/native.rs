@@ -0,0 +1,486 @@
+use std::collections::hash_map::Entry;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use rayon::prelude::*;
+
+use super::{
+    backend::{BackendCapabilities, BackendKind, ScanBackend},
+    dir::{self, Dir, EntryKind, Name, NameBuf, Stat},
+    error::ScanError,
+    exclude::ExcludeMode,
+    inodes::{charge_file, file_node},
+    limits::{limited_children, Child},
+    lock,
+    options::{SymlinkPolicy, TreeLimits},
+    root_display_name,
+    size::ByteSizes,
+    symlink_target, FileNode, ScanState,
+};
+
+// Enough for directories nested thousands of levels deep
+const WALKER_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+// Directory descriptors kept open for opening subdirectories relative to
+// them, well under the usual soft limit of 1024 open files
+const MAX_HELD_DIRS: usize = 256;
+
+// How many bytes of path below the nearest held directory a subdirectory is
+// still opened through, well under the 4096 Linux allows
+const MAX_ANCHOR_DISTANCE: usize = 2048;
+
+pub struct NativeBackend;
+
+impl ScanBackend for NativeBackend {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Native
+    }
+
+    fn name(&self) -> &'static str {
+        "Native walker"
+    }
+
+    fn is_available(&self, _app_handle: &tauri::AppHandle) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            full_depth: true,
+            apparent_size: true,
+            allocated_size: true,
+            streaming: false,
+        }
+    }
+
+    fn scan(
+        &self,
+        path: &Path,
+        app_handle: &tauri::AppHandle,
+        state: &mut ScanState,
+    ) -> Result<FileNode, ScanError> {
+        scan_directory_native(path, app_handle, state)
+    }
+}
+
+// In-process walker that builds the complete FileNode tree. Directories are
+// walked in parallel on a work-stealing pool: each listing hands its
+// subdirectories out as separate tasks for idle threads to take, and every
+// task returns its finished subtree to its parent, so sizes add up without
+// any shared totals.
+pub fn scan_directory_native(
+    path: &Path,
+    app_handle: &tauri::AppHandle,
+    state: &mut ScanState,
+) -> Result<FileNode, ScanError> {
+    walk_tree(path, Some(app_handle), state)
+}
+
+// The walk itself; without an app handle nothing is reported along the way
+fn walk_tree(
+    path: &Path,
+    app_handle: Option<&tauri::AppHandle>,
+    state: &mut ScanState,
+) -> Result<FileNode, ScanError> {
+    println!("[NATIVE] Walking directory tree: {}", path.display());
+
+    let metadata = fs::metadata(path).map_err(|e| ScanError::io(path, &e))?;
+    if !metadata.is_dir() {
+        return Ok(FileNode::new(
+            root_display_name(path),
+            path,
+            false,
+            ByteSizes::of(&metadata),
+            state.metric(),
+        ));
+    }
+
+    // rayon picks one thread per CPU for zero. Each level of the tree is a
+    // few frames on a walker's stack, so stacks are sized for deep trees.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(state.options.threads.unwrap_or(0))
+        .thread_name(|index| format!("scan-walker-{}", index))
+        .stack_size(WALKER_STACK_SIZE)
+        .build()
+        .map_err(|e| ScanError::other(path, format!("Could not start walker threads: {}", e)))?;
+    let walk = Walk {
+        state,
+        app_handle,
+        limits: state.options.tree_limits(),
+        held_dirs: AtomicUsize::new(0),
+        interrupted: Mutex::new(None),
+    };
+    let mut root = pool.install(|| walk_directory(&walk, path, &Stat::of(&metadata), 0, None, &|| Dir::open(path)));
+
+    root.name = root_display_name(path);
+
+    // A stopped walk still returns everything it reached, which becomes the
+    // partial tree as it is, mount points and unreadable directories included
+    if let Some(e) = walk.interrupted.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner()) {
+        println!("[NATIVE] Walk stopped: {}", e);
+        state.walked_root = Some(root);
+        return Err(e);
+    }
+
+    println!(
+        "[NATIVE] Walk complete: {} items, total size: {}",
+        state.counters.items(),
+        super::format_size(root.size)
+    );
+
+    Ok(root)
+}
+
+// What the threads of one walk share
+struct Walk<'a> {
+    state: &'a ScanState,
+    app_handle: Option<&'a tauri::AppHandle>,
+    // Applied to each directory as it finishes, so the tree never holds
+    // nodes that would be dropped
+    limits: TreeLimits,
+    // Directories holding their descriptor until their subdirectories are done
+    held_dirs: AtomicUsize,
+    // Why the walk stopped early, once it has
+    interrupted: Mutex<Option<ScanError>>,
+}
+
+impl Walk<'_> {
+    // Whether another directory may keep its descriptor while its
+    // subdirectories are walked; always when it `must`
+    fn hold_dir(&self, must: bool) -> bool {
+        self.held_dirs
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |held| {
+                (must || held < MAX_HELD_DIRS).then_some(held + 1)
+            })
+            .is_ok()
+    }
+
+    fn should_stop(&self) -> bool {
+        match self.state.check_interrupted() {
+            Ok(()) => false,
+            Err(e) => {
+                lock(&self.interrupted).get_or_insert(e);
+                true
+            }
+        }
+    }
+}
+
+// The nearest directory up the tree still holding its descriptor
+#[derive(Clone, Copy)]
+struct Anchor<'a> {
+    dir: &'a Dir,
+    path: &'a Path,
+}
+
+// Walks a directory `level` levels below the root, returning a node with the
+// descendants the tree limits keep. `open` opens it once it is known to need
+// walking. An interrupted walk stops listing and returns what it has.
+fn walk_directory(
+    walk: &Walk,
+    path: &Path,
+    stat: &Stat,
+    level: u32,
+    anchor: Option<Anchor>,
+    open: &dyn Fn() -> io::Result<Dir>,
+) -> FileNode {
+    let state = walk.state;
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut children = Vec::new();
+    let mut subdirs = Vec::new();
+
+    if walk.should_stop() {
+        return FileNode::new(name, path, true, ByteSizes::default(), state.metric());
+    }
+
+    // Once links are followed the same directory can be reached twice, by a
+    // cycle or by a second link; it is only walked the first time, and
+    // whichever path gets there later points to where it was counted
+    if state.options.symlink_policy != SymlinkPolicy::Never {
+        if let Some(id) = stat.id {
+            match lock(&state.visited_dirs).entry(id) {
+                Entry::Occupied(first) => {
+                    println!("[NATIVE] Already walked {}, not descending again", path.display());
+                    let mut node = FileNode::new(name, path, true, ByteSizes::default(), state.metric());
+                    node.counted_at = Some(first.get().to_string_lossy().to_string());
+                    return node;
+                }
+                Entry::Vacant(slot) => {
+                    slot.insert(path.to_path_buf());
+                }
+            }
+        }
+    }
+
+    state.counters.add_directory();
+
+    // An unreadable directory is kept as an inaccessible node, still
+    // charged for its own blocks
+    let unreadable = |e: &io::Error| {
+        let mut node = state.skip(path, true, e);
+        node.set_sizes(stat.sizes, state.metric());
+        node
+    };
+    let dir = match open() {
+        Ok(dir) => dir,
+        Err(e) => return unreadable(&e),
+    };
+    let mut entries = match dir.entries() {
+        Ok(entries) => entries,
+        Err(e) => return unreadable(&e),
+    };
+
+    // Entries are reached through the directory handle by name; their paths
+    // are put together in one reused buffer, and only kept for nodes
+    let mut entry_path = path.to_path_buf();
+    let mut listing_failed = false;
+    while let Some(entry) = entries.next_entry() {
+        if walk.should_stop() {
+            break;
+        }
+        let (entry_name, kind) = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                // The listing itself failed part way; keep what was read
+                state.skip(path, true, &e);
+                listing_failed = true;
+                break;
+            }
+        };
+
+        entry_path.push(dir::name_os_str(entry_name));
+        let child = entry_node(walk, &dir, entry_name, kind, &entry_path, level + 1, &mut subdirs);
+        entry_path.pop();
+        children.extend(child);
+
+        if let Some(app_handle) = walk.app_handle.filter(|_| state.should_emit()) {
+            state.emit(app_handle, &path.to_string_lossy());
+        }
+    }
+    drop(entries);
+
+    // Every directory in progress holding its descriptor would run out of
+    // them in a deep tree, so past a budget subdirectories are opened
+    // through the nearest directory up the tree that still holds one, as
+    // long as the path from there stays short
+    let too_far = anchor.is_none_or(|anchor| {
+        path.as_os_str().len().saturating_sub(anchor.path.as_os_str().len()) > MAX_ANCHOR_DISTANCE
+    });
+    let held = !subdirs.is_empty() && walk.hold_dir(too_far);
+    let dir = held.then_some(dir);
+    let anchor = dir.as_ref().map(|dir| Anchor { dir, path }).or(anchor);
+
+    // Each subdirectory is a task of its own that any idle thread can steal
+    let subtrees: Vec<Child> = subdirs
+        .into_par_iter()
+        .map(|subdir: Subdir| {
+            let open = || match (&dir, anchor) {
+                (Some(dir), _) => dir.open_at(&subdir.name, false),
+                (None, Some(anchor)) => {
+                    anchor.dir.open_below(subdir.path.strip_prefix(anchor.path).unwrap_or(&subdir.path))
+                }
+                (None, None) => Dir::open(&subdir.path),
+            };
+            let mut child = walk_directory(walk, &subdir.path, &subdir.stat, level + 1, anchor, &open);
+            if subdir.exclusion == Some(ExcludeMode::Collapse) {
+                child.collapse();
+            }
+            Child::Node(child)
+        })
+        .collect();
+    children.extend(subtrees);
+    if held {
+        walk.held_dirs.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    // The directory's own entry counts towards it, as it does for du, and
+    // is listed as a child of its own
+    let metric = state.metric();
+    let mut total_size = stat.sizes;
+    let mut shared_size = 0;
+    for child in &children {
+        total_size += child.sizes();
+        shared_size += child.shared_size();
+    }
+
+    if let Some(app_handle) = walk.app_handle {
+        state.backfill_directory(app_handle, path, total_size);
+    }
+
+    let mut node = FileNode::new(name, path, true, total_size, metric);
+    node.children = limited_children(&walk.limits, path, level, children, metric);
+    node.add_own_blocks(stat.sizes, metric);
+    node.shared_size = shared_size;
+    node.inaccessible = listing_failed;
+    node
+}
+
+// A subdirectory left to walk once its parent's listing is done
+struct Subdir {
+    name: NameBuf,
+    path: PathBuf,
+    stat: Stat,
+    exclusion: Option<ExcludeMode>,
+}
+
+// One directory entry `level` levels below the root, or none for an excluded
+// entry or a subdirectory, which is queued in `subdirs` instead. `kind` is
+// the entry's type as listed, if known.
+fn entry_node(
+    walk: &Walk,
+    dir: &Dir,
+    name: &Name,
+    kind: Option<EntryKind>,
+    path: &Path,
+    level: u32,
+    subdirs: &mut Vec<Subdir>,
+) -> Option<Child> {
+    let state = walk.state;
+
+    // A mount point of an excluded filesystem is told from the listing and
+    // the mount table alone, as a stat could hang on a stale network mount
+    let may_be_mount = kind.is_none_or(|kind| kind == EntryKind::Directory);
+    let stat = if may_be_mount && state.is_excluded_mount(path) {
+        None
+    } else {
+        // Not following symlinks here; links are only descended through
+        // when the symlink policy allows it
+        match dir.stat_at(name, false) {
+            Ok(stat) => Some(stat),
+            Err(e) => return Some(Child::Node(state.skip(path, false, &e))),
+        }
+    };
+
+    let is_dir = stat.is_none_or(|stat| stat.kind == EntryKind::Directory);
+    let exclusion = state.exclusions.check(path, is_dir);
+    if exclusion == Some(ExcludeMode::Skip) {
+        return None;
+    }
+
+    let mut node = match stat {
+        None => state.excluded_mount_node(path)?,
+        Some(stat) if stat.kind == EntryKind::Directory => match state.mount_point_node(path, stat.device) {
+            Some(node) => node,
+            None => {
+                subdirs.push(Subdir { name: name.to_owned(), path: path.to_path_buf(), stat, exclusion });
+                return None;
+            }
+        },
+        Some(stat) if stat.kind == EntryKind::Symlink => walk_symlink(walk, dir, name, path, &stat, level),
+        Some(stat) if exclusion.is_none() => {
+            let (sizes, shared) = charge_file(state, &stat);
+            return Some(Child::File { name: name.to_owned(), sizes, shared_size: shared.get(state.metric()) });
+        }
+        Some(stat) => file_node(state, path, &stat),
+    };
+
+    if exclusion == Some(ExcludeMode::Collapse) {
+        node.collapse();
+    }
+    Some(Child::Node(node))
+}
+
+// A symlink is a leaf showing its target unless the symlink policy follows
+// it, in which case it stands in for whatever it points at. Dangling links
+// stay leaves.
+fn walk_symlink(walk: &Walk, dir: &Dir, name: &Name, path: &Path, stat: &Stat, level: u32) -> FileNode {
+    let state = walk.state;
+    let target = if state.follows(path) { dir.stat_at(name, true).ok() } else { None };
+    let mut node = match target {
+        Some(target) if target.kind == EntryKind::Directory => {
+            walk_directory(walk, path, &target, level, None, &|| dir.open_at(name, true))
+        }
+        Some(target) => file_node(state, path, &target),
+        None => file_node(state, path, stat),
+    };
+    node.symlink_target = symlink_target(path);
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{exclude::Exclusions, manager::ScanManager, options::ScanOptions};
+
+    // An empty directory of its own for one test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("scanner-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Walks `root` the way a scan with `options` would
+    fn walk(root: &Path, options: ScanOptions) -> FileNode {
+        let registration = ScanManager::default().begin(None, root, BackendKind::Native).unwrap();
+        let mut state = ScanState::new(registration, root, BackendKind::Native, options.clone());
+        state.exclusions = Exclusions::new(root, &options).unwrap();
+        if options.symlink_policy == SymlinkPolicy::WithinRoot {
+            state.real_root = Some(root.to_path_buf());
+        }
+        walk_tree(root, None, &mut state).unwrap()
+    }
+
+    fn assert_children_add_up(node: &FileNode) {
+        let Some(children) = node.children.as_ref() else { return };
+        assert_eq!(children.iter().map(FileNode::sizes).sum::<ByteSizes>(), node.sizes(), "{}", node.path);
+        children.iter().for_each(assert_children_add_up);
+    }
+
+    #[test]
+    fn directory_sizes_are_the_sum_of_their_children() {
+        let dir = test_dir("sums");
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        fs::create_dir(dir.join("empty")).unwrap();
+        fs::write(dir.join("a/one"), vec![0u8; 5000]).unwrap();
+        fs::write(dir.join("a/b/two"), vec![0u8; 100]).unwrap();
+        fs::write(dir.join("three"), b"3").unwrap();
+
+        let complete = walk(&dir, ScanOptions { complete_tree: true, ..Default::default() });
+        let mut limited = walk(&dir, ScanOptions { depth: Some(1), max_children: Some(1), ..Default::default() });
+        limited.apply_limits(&ScanOptions { depth: Some(1), max_children: Some(1), ..Default::default() }.tree_limits());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(complete.children.iter().flatten().any(|child| child.own_blocks));
+        assert_children_add_up(&complete);
+        assert_children_add_up(&limited);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn linked_file_is_charged_once() {
+        for symlink_policy in [SymlinkPolicy::Always, SymlinkPolicy::WithinRoot] {
+            let dir = test_dir("linked-file");
+            fs::write(dir.join("file"), vec![0u8; 4096]).unwrap();
+            std::os::unix::fs::symlink(dir.join("file"), dir.join("link")).unwrap();
+            let root = walk(&dir, ScanOptions { symlink_policy, complete_tree: true, ..Default::default() });
+            fs::remove_dir_all(&dir).unwrap();
+
+            let entries = root.children.iter().flatten().filter(|child| !child.own_blocks);
+            assert_eq!(entries.map(|child| child.apparent_size).sum::<u64>(), 4096, "{:?}", symlink_policy);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn linked_directory_is_walked_once() {
+        for symlink_policy in [SymlinkPolicy::Always, SymlinkPolicy::WithinRoot] {
+            let dir = test_dir("linked-dir");
+            fs::create_dir(dir.join("real")).unwrap();
+            fs::write(dir.join("real/file"), vec![0u8; 4096]).unwrap();
+            std::os::unix::fs::symlink(dir.join("real"), dir.join("link")).unwrap();
+            let root = walk(&dir, ScanOptions { symlink_policy, complete_tree: true, ..Default::default() });
+            fs::remove_dir_all(&dir).unwrap();
+
+            let (counted, again): (Vec<&FileNode>, Vec<&FileNode>) =
+                root.children.iter().flatten().filter(|child| child.is_dir).partition(|child| child.counted_at.is_none());
+            assert_eq!((counted.len(), again.len()), (1, 1), "{:?}", symlink_policy);
+            assert!(counted[0].apparent_size >= 4096);
+            assert_eq!(again[0].size, 0);
+            assert_eq!(again[0].counted_at.as_deref(), Some(counted[0].path.as_str()));
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    exclude,
+    mounts::Mount,
+    paths::{self, PathArg},
+    size::{ByteSizes, SizeMetric},
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FileNode {
+    pub name: String,
+    // Display form of the path; lossy when the OS path is not valid UTF-8
+    pub path: String,
+    // Base64 of the exact OS path bytes, set only when `path` is lossy.
+    // Commands accept it back as `{ "raw": ... }` (paths::PathArg).
+    #[serde(rename = "rawPath", skip_serializing_if = "Option::is_none")]
+    pub raw_path: Option<String>,
+    pub size: u64,
+    // Logical length and bytes allocated on disk; `size` is whichever of the
+    // two the scan's size metric selects
+    #[serde(rename = "apparentSize")]
+    pub apparent_size: u64,
+    #[serde(rename = "allocatedSize")]
+    pub allocated_size: u64,
+    // Bytes of files under this node that have other hard links. Each such
+    // file is charged to `size` and counted here only at the first link the
+    // scan reached.
+    #[serde(rename = "sharedSize", default, skip_serializing_if = "is_zero")]
+    pub shared_size: u64,
+    #[serde(rename = "isDir")]
+    pub is_dir: bool,
+    pub children: Option<Vec<FileNode>>,
+    // Set on entries the scan could not read; the reason is in ScanResult.skipped
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inaccessible: bool,
+    // Set on mount points the scan did not enter, either because of
+    // one-filesystem mode or the kind of filesystem: the filesystem type
+    // mounted there. These nodes carry no size.
+    #[serde(rename = "fsType", skip_serializing_if = "Option::is_none")]
+    pub fs_type: Option<String>,
+    // Set on symbolic links: where the link points, as stored in the link
+    #[serde(rename = "symlinkTarget", skip_serializing_if = "Option::is_none")]
+    pub symlink_target: Option<String>,
+    // Set on directories reached again once symlinks are followed: the path
+    // their contents were counted under. These nodes carry no size.
+    #[serde(rename = "countedAt", skip_serializing_if = "Option::is_none")]
+    pub counted_at: Option<String>,
+    // Set on subtrees an exclusion rule collapsed: the size is complete but
+    // the children are left out
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub collapsed: bool,
+    // Set on dot-entries when the scan asked for them to be de-emphasized
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hidden: bool,
+    // Set on the aggregate node standing in for the smallest children a tree
+    // limit or a tool left out: how many entries it covers, or 1 when the
+    // tool did not say. It has no path.
+    #[serde(rename = "otherCount", default, skip_serializing_if = "is_zero")]
+    pub other_count: u64,
+    // Set on the node holding the part of a directory's total that a
+    // large-file preview has not yet attributed to any file
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub unattributed: bool,
+    // Set on the node holding the blocks of a directory's own entry, listed
+    // beside its children so they add up to the directory's size
+    #[serde(rename = "ownBlocks", default, skip_serializing_if = "std::ops::Not::not")]
+    pub own_blocks: bool,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+impl FileNode {
+    pub(super) fn new(name: String, path: &Path, is_dir: bool, sizes: ByteSizes, metric: SizeMetric) -> Self {
+        let mut node = FileNode {
+            name,
+            size: sizes.get(metric),
+            apparent_size: sizes.apparent,
+            allocated_size: sizes.allocated,
+            is_dir,
+            ..Default::default()
+        };
+        node.set_path(path);
+        node
+    }
+
+    pub(super) fn set_path(&mut self, path: &Path) {
+        self.path = path.to_string_lossy().to_string();
+        self.raw_path = paths::raw_path(path);
+    }
+
+    // The exact OS path, recovered from `raw_path` when the display form is lossy
+    pub(super) fn os_path(&self) -> PathBuf {
+        let arg = match &self.raw_path {
+            Some(raw) => PathArg::Raw { raw: raw.clone() },
+            None => PathArg::Text(self.path.clone()),
+        };
+        arg.into_path_buf().unwrap_or_else(|_| PathBuf::from(&self.path))
+    }
+
+    pub(super) fn sizes(&self) -> ByteSizes {
+        ByteSizes::new(self.apparent_size, self.allocated_size)
+    }
+
+    pub(super) fn set_sizes(&mut self, sizes: ByteSizes, metric: SizeMetric) {
+        self.size = sizes.get(metric);
+        self.apparent_size = sizes.apparent;
+        self.allocated_size = sizes.allocated;
+    }
+
+    // Flags every dot-entry below this node for de-emphasis
+    pub(super) fn mark_hidden(&mut self) {
+        for child in self.children.iter_mut().flatten() {
+            child.hidden = exclude::is_hidden(&child.os_path());
+            child.mark_hidden();
+        }
+    }
+
+    // Keeps the size of an excluded subtree but drops its contents
+    pub(super) fn collapse(&mut self) {
+        self.children = None;
+        self.collapsed = true;
+    }
+
+    // Leaf standing in for another filesystem mounted at `path`
+    pub(super) fn mount_point(path: &Path, mount: Option<&Mount>) -> Self {
+        let mut node = FileNode {
+            name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            is_dir: true,
+            fs_type: Some(mount.map(|m| m.fs_type.clone()).unwrap_or_else(|| "unknown".to_string())),
+            ..Default::default()
+        };
+        node.set_path(path);
+        node
+    }
+
+    // Placeholder for an entry that could not be read
+    pub(super) fn inaccessible(path: &Path, is_dir: bool) -> Self {
+        let mut node = FileNode {
+            name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            is_dir,
+            inaccessible: true,
+            ..Default::default()
+        };
+        node.set_path(path);
+        node
+    }
+}
@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    backend::BackendKind,
+    exclude::{ExcludeMode, ExcludeRule, IgnoreFile},
+    size::SizeMetric,
+};
+
+// Which symbolic links a scan descends through
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum SymlinkPolicy {
+    // Links are leaves showing their target
+    #[default]
+    Never,
+    // Follow links whose target resolves to somewhere inside the scan root
+    WithinRoot,
+    Always,
+}
+
+// How entries whose names start with a dot are treated
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum HiddenEntries {
+    #[default]
+    Show,
+    // Scanned as usual but flagged so the UI can de-emphasize them
+    Dim,
+    // Left out of the tree and its totals
+    Hide,
+}
+
+// Keep a default tree small enough for the treemap to lay out quickly. It
+// draws two levels below the root; sizes still cover everything deeper.
+pub const DEFAULT_DEPTH: u32 = 2;
+pub const DEFAULT_MAX_CHILDREN: usize = 100;
+
+// How much of the scanned tree is reported, where None means no limit.
+// Limits only drop nodes; the sizes of the ones kept still cover everything.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeLimits {
+    pub depth: Option<u32>,
+    pub max_children: Option<usize>,
+    pub min_size: u64,
+}
+
+// Per-scan settings passed from the frontend; every field is optional
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ScanOptions {
+    // Backend to scan with; picked automatically when unset
+    pub backend: Option<BackendKind>,
+    // Levels below the root to report; DEFAULT_DEPTH when unset
+    pub depth: Option<u32>,
+    // Entries reported per directory, largest first; DEFAULT_MAX_CHILDREN
+    // when unset
+    pub max_children: Option<usize>,
+    // Entries smaller than this many bytes (in the selected size metric) are
+    // left out of the tree; none are when unset
+    pub min_size: Option<u64>,
+    // Report every level and every entry, overriding the three limits above
+    pub complete_tree: bool,
+    // Wall-clock limit in milliseconds; the scan fails with a timeout error
+    // and any external tool is killed once it is exceeded
+    pub timeout_ms: Option<u64>,
+    // Soft limit in milliseconds; once reached the scan stops and returns the
+    // partial tree it has so far, flagged as incomplete
+    pub time_budget_ms: Option<u64>,
+    // Whether node sizes (and so the treemap) use apparent or allocated bytes
+    pub size_metric: SizeMetric,
+    // Stop at mount points instead of descending into other filesystems;
+    // defaults to on when scanning `/`
+    pub one_filesystem: Option<bool>,
+    // Descend into proc, sysfs, tmpfs and other pseudo filesystems
+    pub include_pseudo_filesystems: bool,
+    // Descend into network and FUSE mounts, which can hang when stale
+    pub include_network_filesystems: bool,
+    // Which symlinks to follow; directories reached twice are walked once
+    pub symlink_policy: SymlinkPolicy,
+    // Globs in gitignore syntax, each skipping or collapsing what it matches
+    pub exclude: Vec<ExcludeRule>,
+    // A gitignore-style file of further patterns
+    pub ignore_file: Option<IgnoreFile>,
+    // Treat directories holding a CACHEDIR.TAG as excluded, in this mode
+    pub cachedir_tag: Option<ExcludeMode>,
+    // Dotfiles and dot-directories are scanned and shown unless set otherwise
+    pub hidden: HiddenEntries,
+    // Threads the native walk spreads directories over; one per CPU when
+    // unset
+    pub threads: Option<usize>,
+    // Follow the large-file preview of an mdfind scan with a native walk that
+    // fills in exact totals. Off by default, as the walk takes as long as a
+    // native scan.
+    pub backfill: bool,
+}
+
+impl ScanOptions {
+    pub fn tree_limits(&self) -> TreeLimits {
+        if self.complete_tree {
+            return TreeLimits { depth: None, max_children: None, min_size: 0 };
+        }
+        TreeLimits {
+            depth: Some(self.depth.unwrap_or(DEFAULT_DEPTH)),
+            max_children: Some(self.max_children.unwrap_or(DEFAULT_MAX_CHILDREN)),
+            min_size: self.min_size.unwrap_or(0),
+        }
+    }
+}
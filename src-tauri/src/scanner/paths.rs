@@ -0,0 +1,214 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use super::error::ScanError;
+
+// A path as the frontend sends it back to a command: plain text for paths
+// that are valid UTF-8, or `{ "raw": "<base64>" }` carrying the exact OS bytes
+// of one that is not (FileNode.raw_path)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum PathArg {
+    Text(String),
+    Raw { raw: String },
+}
+
+impl PathArg {
+    pub fn into_path_buf(self) -> Result<PathBuf, ScanError> {
+        match self {
+            PathArg::Text(text) => Ok(PathBuf::from(text)),
+            PathArg::Raw { raw } => {
+                let bytes = STANDARD.decode(raw.as_bytes()).map_err(|e| {
+                    ScanError::other(Path::new(&raw), format!("Invalid raw path encoding: {}", e))
+                })?;
+                Ok(path_from_raw_bytes(bytes))
+            }
+        }
+    }
+}
+
+// Base64 of the OS bytes of a path that `to_string_lossy` would mangle
+#[cfg(unix)]
+pub fn raw_path(path: &Path) -> Option<String> {
+    use std::os::unix::ffi::OsStrExt;
+    match path.to_str() {
+        Some(_) => None,
+        None => Some(STANDARD.encode(path.as_os_str().as_bytes())),
+    }
+}
+
+// Paths elsewhere are always Unicode as far as this app is concerned
+#[cfg(not(unix))]
+pub fn raw_path(_path: &Path) -> Option<String> {
+    None
+}
+
+#[cfg(unix)]
+fn path_from_raw_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(std::ffi::OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_raw_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+// A scan root after normalization: `path` is canonical and is what gets
+// walked, so prefix checks against tool output and the file cache line up;
+// `display` is the root as the user spelled it, with `~`, `.` and `..`
+// resolved but symlinks left alone, used to name the root
+#[derive(Debug, Clone)]
+pub struct ScanRoot {
+    pub path: PathBuf,
+    pub display: PathBuf,
+}
+
+// Expands `~`, makes the path absolute and resolves symlinks
+pub fn resolve_root(path: &Path) -> Result<ScanRoot, ScanError> {
+    let absolute = absolute(path)?;
+    let path = fs::canonicalize(&absolute).map_err(|e| ScanError::io(&absolute, &e))?;
+    // `..` after a symlink means something else lexically than on disk, in
+    // which case only the canonical form is accurate
+    let display = lexical_clean(&absolute);
+    let display = if fs::canonicalize(&display).is_ok_and(|real| real == path) { display } else { path.clone() };
+    Ok(ScanRoot { path, display })
+}
+
+// Like resolve_root, but the final component is kept as is so that commands
+// acting on a symlink act on the link and not on its target. The parent is
+// resolved by the filesystem, `..` included, so the entry is the one the OS
+// would reach; a path ending in `..` names a directory and is resolved whole.
+pub fn resolve_entry(path: &Path) -> Result<PathBuf, ScanError> {
+    let absolute = absolute(path)?;
+    match (absolute.parent(), absolute.file_name()) {
+        (Some(parent), Some(name)) => {
+            let parent = fs::canonicalize(parent).map_err(|e| ScanError::io(&absolute, &e))?;
+            Ok(parent.join(name))
+        }
+        _ => fs::canonicalize(&absolute).map_err(|e| ScanError::io(&absolute, &e)),
+    }
+}
+
+fn absolute(path: &Path) -> Result<PathBuf, ScanError> {
+    let expanded = expand_tilde(path)?;
+    std::path::absolute(&expanded).map_err(|e| ScanError::io(&expanded, &e))
+}
+
+// `~` and `~/rest` expand to the home directory, `~user` to that user's
+fn expand_tilde(path: &Path) -> Result<PathBuf, ScanError> {
+    let mut components = path.components();
+    let Some(Component::Normal(first)) = components.next() else { return Ok(path.to_path_buf()) };
+    let Some(user) = first.to_str().and_then(|first| first.strip_prefix('~')) else {
+        return Ok(path.to_path_buf());
+    };
+    let home = if user.is_empty() { dirs::home_dir() } else { user_home(user) };
+    let home = home.ok_or_else(|| ScanError::other(path, "Could not find the home directory"))?;
+    Ok(home.join(components.as_path()))
+}
+
+#[cfg(unix)]
+fn user_home(user: &str) -> Option<PathBuf> {
+    use std::ffi::{CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+
+    let name = CString::new(user).ok()?;
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result = std::ptr::null_mut();
+    let mut buffer = vec![0 as libc::c_char; 16 * 1024];
+    let status = unsafe {
+        libc::getpwnam_r(name.as_ptr(), &mut passwd, buffer.as_mut_ptr(), buffer.len(), &mut result)
+    };
+    if status != 0 || result.is_null() {
+        return None;
+    }
+    let dir = unsafe { CStr::from_ptr(passwd.pw_dir) };
+    Some(PathBuf::from(std::ffi::OsStr::from_bytes(dir.to_bytes())))
+}
+
+#[cfg(not(unix))]
+fn user_home(_user: &str) -> Option<PathBuf> {
+    None
+}
+
+// Drops `.` components and applies `..` to the preceding component. A `..`
+// at the root stays at the root, as it does for the kernel.
+fn lexical_clean(path: &Path) -> PathBuf {
+    let mut clean = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match clean.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    clean.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => clean.push(component),
+            },
+            _ => clean.push(component),
+        }
+    }
+    clean
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lexical_clean_applies_dot_components() {
+        assert_eq!(lexical_clean(Path::new("/a/./b/../c")), PathBuf::from("/a/c"));
+        assert_eq!(lexical_clean(Path::new("/a/b/../../..")), PathBuf::from("/"));
+        assert_eq!(lexical_clean(Path::new("/..")), PathBuf::from("/"));
+        assert_eq!(lexical_clean(Path::new("../../a/..")), PathBuf::from("../.."));
+        assert_eq!(lexical_clean(Path::new("a/..")), PathBuf::new());
+    }
+
+    #[test]
+    fn unicode_paths_travel_as_text() {
+        assert_eq!(raw_path(Path::new("/tmp/caf\u{e9}")), None);
+        let arg: PathArg = serde_json::from_str(r#""/tmp/café""#).unwrap();
+        assert_eq!(arg.into_path_buf().unwrap(), PathBuf::from("/tmp/caf\u{e9}"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_paths_round_trip_through_raw_bytes() {
+        use crate::scanner::{size::{ByteSizes, SizeMetric}, FileNode};
+        use std::os::unix::ffi::OsStrExt;
+
+        let path = Path::new(std::ffi::OsStr::from_bytes(b"/tmp/bad\xff\xfename"));
+        let node = FileNode::new("bad".to_string(), path, false, ByteSizes::default(), SizeMetric::Apparent);
+        assert_eq!(node.path, "/tmp/bad\u{fffd}\u{fffd}name");
+        assert_eq!(node.os_path(), path);
+
+        // The frontend sends the node's rawPath back as `{ "raw": ... }`
+        let json = format!(r#"{{"raw":"{}"}}"#, node.raw_path.unwrap());
+        let arg: PathArg = serde_json::from_str(&json).unwrap();
+        assert_eq!(arg.into_path_buf().unwrap(), path);
+        assert!(PathArg::Raw { raw: "not base64!".to_string() }.into_path_buf().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn entry_after_a_symlink_and_dot_dot_is_the_one_on_disk() {
+        let base = fs::canonicalize(std::env::temp_dir()).unwrap().join(format!("paths-entry-{}", std::process::id()));
+        fs::create_dir_all(base.join("a")).unwrap();
+        fs::create_dir_all(base.join("target/inner")).unwrap();
+        fs::write(base.join("a/victim"), b"").unwrap();
+        fs::write(base.join("target/victim"), b"").unwrap();
+        std::os::unix::fs::symlink(base.join("target/inner"), base.join("a/link")).unwrap();
+
+        let entry = resolve_entry(&base.join("a/link/../victim"));
+        let link = resolve_entry(&base.join("a/./link"));
+        let dir = resolve_entry(&base.join("a/link/.."));
+        fs::remove_dir_all(&base).unwrap();
+
+        assert_eq!(entry.unwrap(), base.join("target/victim"));
+        assert_eq!(link.unwrap(), base.join("a/link"));
+        assert_eq!(dir.unwrap(), base.join("target"));
+    }
+}
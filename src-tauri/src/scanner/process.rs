@@ -0,0 +1,189 @@
+use std::ffi::OsStr;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use super::{error::ScanError, ScanState};
+
+// How often a running tool is checked for exit, cancellation and timeout
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// Process IDs of the external tools a scan has running. Each tool leads its
+// own process group, so killing the group also stops anything it spawned.
+#[derive(Debug, Default)]
+pub struct ChildProcesses {
+    pids: Mutex<Vec<u32>>,
+}
+
+impl ChildProcesses {
+    fn register(&self, pid: u32) {
+        if let Ok(mut pids) = self.pids.lock() {
+            pids.push(pid);
+        }
+    }
+
+    fn unregister(&self, pid: u32) {
+        if let Ok(mut pids) = self.pids.lock() {
+            pids.retain(|p| *p != pid);
+        }
+    }
+
+    // Kills every registered tool along with its process group
+    pub fn kill_all(&self) {
+        let Ok(pids) = self.pids.lock() else { return };
+        for pid in pids.iter() {
+            println!("[PROCESS] Killing process group {}", pid);
+            kill_process_group(*pid);
+        }
+    }
+}
+
+// What an external tool printed
+pub struct ToolOutput {
+    pub stdout: Vec<u8>,
+    // Tools report unreadable paths here while still printing the rest
+    pub stderr: Vec<u8>,
+}
+
+// Runs an external tool with an argument vector and returns its output.
+// Nothing goes through a shell, so paths are passed to the tool verbatim.
+// The tool is killed as soon as the scan is cancelled or times out.
+pub fn run_command(command: &mut Command, state: &ScanState) -> Result<ToolOutput, ScanError> {
+    let program = command.get_program().to_string_lossy().to_string();
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    // A tool that cannot be started means its backend is unusable
+    let child = command.spawn().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => ScanError::backend_unavailable(&state.path, state.backend),
+        _ => ScanError::io(Path::new(&program), &e),
+    })?;
+    let mut tool = RunningTool::new(child, &state.children);
+
+    let (status, output) = wait_for_output(&mut tool, state).map_err(|e| {
+        println!("[PROCESS] Stopped {}: {}", program, e);
+        e
+    })?;
+
+    // Tools like du exit non-zero when a single subdirectory is unreadable, so
+    // the partial output is still used
+    if !status.success() {
+        println!("[PROCESS] {} exited with status: {:?}", program, status);
+    }
+
+    Ok(output)
+}
+
+// A started tool, registered with the scan while it runs. Dropping it before
+// it has been reaped kills its process group and waits for it, so no way out
+// of run_command leaves it behind.
+struct RunningTool<'a> {
+    child: Child,
+    processes: &'a ChildProcesses,
+    reaped: bool,
+}
+
+impl<'a> RunningTool<'a> {
+    fn new(child: Child, processes: &'a ChildProcesses) -> Self {
+        processes.register(child.id());
+        RunningTool { child, processes, reaped: false }
+    }
+
+    fn kill(&mut self) {
+        if !self.reaped {
+            kill_process_group(self.child.id());
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+            self.reaped = true;
+        }
+    }
+}
+
+impl Drop for RunningTool<'_> {
+    fn drop(&mut self) {
+        self.kill();
+        self.processes.unregister(self.child.id());
+    }
+}
+
+// Drains stdout and stderr on helper threads while polling the child, so a
+// full pipe can never stall the poll loop. The threads are joined however the
+// wait ends.
+fn wait_for_output(
+    tool: &mut RunningTool,
+    state: &ScanState,
+) -> Result<(std::process::ExitStatus, ToolOutput), ScanError> {
+    let stdout = drain(tool.child.stdout.take());
+    let stderr = drain(tool.child.stderr.take());
+
+    let status = wait_for_exit(tool, state);
+    if status.is_err() {
+        // Closes the pipes so the readers finish
+        tool.kill();
+    }
+
+    let read_failed = |_| ScanError::other(&state.path, "Failed to read tool output");
+    let stdout = stdout.join().map_err(read_failed);
+    let stderr = stderr.join().map_err(read_failed);
+    Ok((status?, ToolOutput { stdout: stdout?, stderr: stderr? }))
+}
+
+// Polls the tool until it exits or the scan is stopped
+fn wait_for_exit(tool: &mut RunningTool, state: &ScanState) -> Result<std::process::ExitStatus, ScanError> {
+    loop {
+        state.check_interrupted()?;
+        match tool.child.try_wait() {
+            Ok(Some(status)) => {
+                tool.reaped = true;
+                return Ok(status);
+            }
+            Ok(None) => thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(ScanError::io(&state.path, &e)),
+        }
+    }
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+#[cfg(unix)]
+fn kill_process_group(pid: u32) {
+    // A negative PID addresses the whole process group
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn kill_process_group(_pid: u32) {}
+
+// Splits NUL-terminated records (du -0, mdfind -0), skipping empty ones
+pub fn nul_records(bytes: &[u8]) -> impl Iterator<Item = &[u8]> {
+    bytes.split(|b| *b == 0).filter(|record| !record.is_empty())
+}
+
+// Rebuilds a path from raw tool output without any lossy conversion
+#[cfg(unix)]
+pub fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+pub fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
@@ -0,0 +1,106 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use super::backend::BackendKind;
+
+// Coarse stage of a scan, so the UI never has to infer it from text
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScanPhase {
+    Starting,
+    Scanning,
+    BuildingTree,
+    Finished,
+}
+
+// Payload of scan-progress events
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    pub scan_id: String,
+    pub phase: ScanPhase,
+    pub backend: BackendKind,
+    #[serde(flatten)]
+    pub totals: ScanTotals,
+    pub current_directory: String,
+    pub elapsed_ms: u64,
+    // Multi-pass backends (mdfind) report which pass is running
+    pub pass: Option<u32>,
+    pub total_passes: Option<u32>,
+}
+
+// Point-in-time copy of a scan's counters
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanTotals {
+    pub files_seen: u64,
+    pub directories_seen: u64,
+    pub bytes_accumulated: u64,
+    pub errors: u64,
+}
+
+// Counters shared between a running scan and the scan manager
+#[derive(Debug, Default)]
+pub struct ScanCounters {
+    files: AtomicU64,
+    directories: AtomicU64,
+    bytes: AtomicU64,
+    errors: AtomicU64,
+}
+
+impl ScanCounters {
+    pub fn add_file(&self, size: u64) {
+        self.files.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_add(size, Ordering::Relaxed);
+    }
+
+    pub fn add_directory(&self) {
+        self.directories.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // External tools report sizes per directory rather than per file
+    pub fn add_bytes(&self, size: u64) {
+        self.bytes.fetch_add(size, Ordering::Relaxed);
+    }
+
+    pub fn add_error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // Starts counting over, for a pass that sees the same files again. Errors
+    // are kept, as every one is still in the scan's skipped list.
+    pub fn reset_progress(&self) {
+        for counter in [&self.files, &self.directories, &self.bytes] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn items(&self) -> u64 {
+        self.files.load(Ordering::Relaxed) + self.directories.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> ScanTotals {
+        ScanTotals {
+            files_seen: self.files.load(Ordering::Relaxed),
+            directories_seen: self.directories.load(Ordering::Relaxed),
+            bytes_accumulated: self.bytes.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resetting_progress_keeps_the_error_count() {
+        let counters = ScanCounters::default();
+        counters.add_file(10);
+        counters.add_error();
+        counters.reset_progress();
+        let totals = counters.snapshot();
+        assert_eq!((totals.files_seen, totals.bytes_accumulated, totals.errors), (0, 0, 1));
+    }
+}
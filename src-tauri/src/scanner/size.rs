@@ -0,0 +1,96 @@
+use std::fs::Metadata;
+use std::iter::Sum;
+use std::ops::{AddAssign, SubAssign};
+
+use serde::{Deserialize, Serialize};
+
+// Which byte count drives FileNode.size and therefore the treemap
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeMetric {
+    // Logical file length, what `ls -l` shows
+    #[default]
+    Apparent,
+    // Blocks actually used on disk (st_blocks * 512), what `du` shows
+    Allocated,
+}
+
+// Both byte counts of an entry or a whole subtree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ByteSizes {
+    pub apparent: u64,
+    pub allocated: u64,
+}
+
+impl ByteSizes {
+    pub fn new(apparent: u64, allocated: u64) -> Self {
+        ByteSizes { apparent, allocated }
+    }
+
+    #[cfg(unix)]
+    pub fn of(metadata: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        ByteSizes::new(metadata.len(), metadata.blocks() * 512)
+    }
+
+    // Block counts are not exposed off Unix, so the length stands in for both
+    #[cfg(not(unix))]
+    pub fn of(metadata: &Metadata) -> Self {
+        ByteSizes::new(metadata.len(), metadata.len())
+    }
+
+    pub fn get(&self, metric: SizeMetric) -> u64 {
+        match metric {
+            SizeMetric::Apparent => self.apparent,
+            SizeMetric::Allocated => self.allocated,
+        }
+    }
+}
+
+// Identifies a file by (st_dev, st_ino)
+#[cfg(unix)]
+pub fn file_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+pub fn file_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+// Identifies a file with more than one hard link, so the walker can charge
+// its bytes only once
+#[cfg(unix)]
+pub fn hard_link_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    file_id(metadata).filter(|_| metadata.nlink() > 1)
+}
+
+#[cfg(not(unix))]
+pub fn hard_link_id(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+impl AddAssign for ByteSizes {
+    fn add_assign(&mut self, other: ByteSizes) {
+        self.apparent += other.apparent;
+        self.allocated += other.allocated;
+    }
+}
+
+impl Sum for ByteSizes {
+    fn sum<I: Iterator<Item = ByteSizes>>(iter: I) -> Self {
+        iter.fold(ByteSizes::default(), |mut total, sizes| {
+            total += sizes;
+            total
+        })
+    }
+}
+
+impl SubAssign for ByteSizes {
+    fn sub_assign(&mut self, other: ByteSizes) {
+        self.apparent = self.apparent.saturating_sub(other.apparent);
+        self.allocated = self.allocated.saturating_sub(other.allocated);
+    }
+}
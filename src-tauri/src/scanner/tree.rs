@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{
+    root_display_name,
+    size::{ByteSizes, SizeMetric},
+    FileNode,
+};
+
+// A directory while the tree is assembled. Directories are only ever added
+// below one that already exists, so a parent always has a lower index than
+// its children.
+struct Dir<'a> {
+    path: &'a Path,
+    parent: usize,
+    sizes: ByteSizes,
+    children: Vec<FileNode>,
+}
+
+// Builds the tree for a flat set of files under `root`, creating every
+// directory on the way to them and summing directory sizes from their files.
+// Files outside `root` are ignored. Each directory's children come out
+// largest first.
+//
+// Runs in time linear in the number of files: directories are found through
+// a path index rather than by searching, and nodes are assembled in a single
+// pass from the deepest directories up.
+pub fn build_tree(files: &HashMap<PathBuf, ByteSizes>, root: &Path, metric: SizeMetric) -> FileNode {
+    let mut dirs = vec![Dir { path: root, parent: 0, sizes: ByteSizes::default(), children: Vec::new() }];
+    let mut index: HashMap<&Path, usize> = HashMap::from([(root, 0)]);
+
+    for (file_path, sizes) in files {
+        let Some(parent) = file_path.parent().filter(|parent| parent.starts_with(root)) else { continue };
+        let dir = dir_index(parent, &mut dirs, &mut index);
+        dirs[dir].sizes += *sizes;
+        dirs[dir].children.push(FileNode::new(
+            file_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            file_path,
+            false,
+            *sizes,
+            metric,
+        ));
+    }
+
+    // Children sit after their parent, so walking backwards finishes every
+    // directory before the one containing it
+    for i in (1..dirs.len()).rev() {
+        let dir = &mut dirs[i];
+        let mut children = std::mem::take(&mut dir.children);
+        children.sort_by_key(|child| std::cmp::Reverse(child.size));
+        let (path, parent, sizes) = (dir.path, dir.parent, dir.sizes);
+
+        let mut node = FileNode::new(
+            path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            path,
+            true,
+            sizes,
+            metric,
+        );
+        node.children = Some(children);
+        dirs[parent].sizes += sizes;
+        dirs[parent].children.push(node);
+    }
+
+    let Dir { sizes, mut children, .. } = dirs.swap_remove(0);
+    children.sort_by_key(|child| std::cmp::Reverse(child.size));
+    let mut root_node = FileNode::new(root_display_name(root), root, true, sizes, metric);
+    root_node.children = if children.is_empty() { None } else { Some(children) };
+    root_node
+}
+
+// Index of the directory at `path`, adding it and any missing ancestors
+fn dir_index<'a>(path: &'a Path, dirs: &mut Vec<Dir<'a>>, index: &mut HashMap<&'a Path, usize>) -> usize {
+    if let Some(&i) = index.get(path) {
+        return i;
+    }
+
+    // Climb to the nearest directory already known, then add the missing
+    // ones top-down so each parent precedes its children
+    let mut missing = vec![path];
+    let mut parent = loop {
+        let dir = missing.last().and_then(|dir| dir.parent()).expect("files are under the root");
+        match index.get(dir) {
+            Some(&i) => break i,
+            None => missing.push(dir),
+        }
+    };
+    for dir in missing.into_iter().rev() {
+        dirs.push(Dir { path: dir, parent, sizes: ByteSizes::default(), children: Vec::new() });
+        parent = dirs.len() - 1;
+        index.insert(dir, parent);
+    }
+    parent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, u64)]) -> HashMap<PathBuf, ByteSizes> {
+        entries.iter().map(|(path, size)| (PathBuf::from(path), ByteSizes::new(*size, *size))).collect()
+    }
+
+    fn child<'a>(node: &'a FileNode, name: &str) -> &'a FileNode {
+        node.children.iter().flatten().find(|child| child.name == name).unwrap()
+    }
+
+    #[test]
+    fn file_directly_under_root_is_a_child_of_root() {
+        let root = build_tree(&files(&[("/scan/a", 10)]), Path::new("/scan"), SizeMetric::Apparent);
+        assert_eq!(root.size, 10);
+        let a = child(&root, "a");
+        assert!(!a.is_dir);
+        assert_eq!(a.path, "/scan/a");
+    }
+
+    #[test]
+    fn deep_file_creates_every_directory_on_the_way() {
+        let root = build_tree(&files(&[("/scan/a/b/c/d/f", 7)]), Path::new("/scan"), SizeMetric::Apparent);
+        let mut node = &root;
+        for name in ["a", "b", "c", "d"] {
+            node = child(node, name);
+            assert!(node.is_dir);
+            assert_eq!(node.size, 7);
+        }
+        assert_eq!(node.path, "/scan/a/b/c/d");
+        assert_eq!(child(node, "f").size, 7);
+    }
+
+    #[test]
+    fn directory_sizes_sum_their_files_and_skip_files_outside_root() {
+        let files = files(&[
+            ("/scan/x/one", 1),
+            ("/scan/x/two", 2),
+            ("/scan/x/y/four", 4),
+            ("/scan/eight", 8),
+            ("/elsewhere/sixteen", 16),
+        ]);
+        let root = build_tree(&files, Path::new("/scan"), SizeMetric::Apparent);
+        assert_eq!(root.size, 15);
+        let x = child(&root, "x");
+        assert_eq!(x.size, 7);
+        assert_eq!(child(x, "y").size, 4);
+        // Largest first
+        let names: Vec<&str> = root.children.iter().flatten().map(|child| child.name.as_str()).collect();
+        assert_eq!(names, ["eight", "x"]);
+    }
+}
@@ -0,0 +1,203 @@
+// Moves files to the user's trash following the freedesktop.org Trash spec
+// (https://specifications.freedesktop.org/trash-spec/latest/). Works on raw
+// path bytes throughout, so any legal Linux filename can be trashed and
+// restored by a file manager.
+
+use std::ffi::{CString, OsStr, OsString};
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+use std::path::{Path, PathBuf};
+
+use crate::scanner::error::ScanError;
+
+pub fn move_to_trash(path: &Path) -> Result<(), ScanError> {
+    let path = std::path::absolute(path).map_err(|e| ScanError::io(path, &e))?;
+    let name = path.file_name().ok_or_else(|| ScanError::other(&path, "Cannot trash a filesystem root"))?;
+    let device = fs::symlink_metadata(&path).map_err(|e| ScanError::io(&path, &e))?.dev();
+
+    let trash = trash_for(&path, device)?;
+    let files = trash.dir.join("files");
+    let info = trash.dir.join("info");
+    for dir in [&files, &info] {
+        fs::create_dir_all(dir).map_err(|e| ScanError::io(dir, &e))?;
+    }
+
+    // Trashes at the top of a filesystem record paths relative to it
+    let original = trash.topdir.as_deref().and_then(|topdir| path.strip_prefix(topdir).ok()).unwrap_or(&path);
+    let (trashed_name, info_path) = reserve_names(&files, &info, name, original)?;
+    let destination = files.join(&trashed_name);
+    if let Err(e) = rename_noreplace(&path, &destination) {
+        let _ = fs::remove_file(&info_path);
+        return Err(match e.raw_os_error() {
+            Some(libc::EXDEV) => cross_device(&path),
+            _ => ScanError::io(&path, &e),
+        });
+    }
+
+    println!("[TRASH] Moved {} to {}", path.display(), destination.display());
+    Ok(())
+}
+
+// A trash directory, and the top of the filesystem it serves unless it is
+// the home trash
+struct Trash {
+    dir: PathBuf,
+    topdir: Option<PathBuf>,
+}
+
+// Items are only ever renamed into a trash on their own filesystem: the home
+// trash when they share its filesystem, otherwise $topdir/.Trash/$uid or
+// $topdir/.Trash-$uid at the top of theirs
+fn trash_for(path: &Path, device: u64) -> Result<Trash, ScanError> {
+    let home = home_trash().ok_or_else(|| ScanError::other(path, "Could not locate the trash directory"))?;
+    fs::create_dir_all(&home).map_err(|e| ScanError::io(&home, &e))?;
+    if fs::metadata(&home).is_ok_and(|m| m.dev() == device) {
+        return Ok(Trash { dir: home, topdir: None });
+    }
+
+    let topdir = top_directory(path, device);
+    let uid = unsafe { libc::getuid() };
+
+    // An administrator-provided .Trash is only used when it is a real sticky
+    // directory
+    let shared = topdir.join(".Trash");
+    let sticky = fs::symlink_metadata(&shared).is_ok_and(|m| m.is_dir() && m.mode() & libc::S_ISVTX != 0);
+    if sticky {
+        let dir = shared.join(uid.to_string());
+        if DirBuilder::new().recursive(true).mode(0o700).create(&dir).is_ok() {
+            return Ok(Trash { dir, topdir: Some(topdir) });
+        }
+    }
+
+    let dir = topdir.join(format!(".Trash-{}", uid));
+    match DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(_) => return Err(cross_device(path)),
+    }
+    if !fs::symlink_metadata(&dir).is_ok_and(|m| m.is_dir()) {
+        return Err(cross_device(path));
+    }
+    Ok(Trash { dir, topdir: Some(topdir) })
+}
+
+// The highest directory above `path` still on its filesystem
+fn top_directory(path: &Path, device: u64) -> PathBuf {
+    let mut topdir = path.parent().unwrap_or(path);
+    while let Some(parent) = topdir.parent() {
+        if !fs::metadata(parent).is_ok_and(|m| m.dev() == device) {
+            break;
+        }
+        topdir = parent;
+    }
+    topdir.to_path_buf()
+}
+
+fn cross_device(path: &Path) -> ScanError {
+    ScanError::Unsupported {
+        path: path.to_string_lossy().to_string(),
+        code: Some(libc::EXDEV),
+        operation: "Moving items on this filesystem to the trash".to_string(),
+        backend: None,
+    }
+}
+
+// $XDG_DATA_HOME/Trash, defaulting to ~/.local/share/Trash
+fn home_trash() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| Path::new(dir).is_absolute())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local/share")))?;
+    Some(data_home.join("Trash"))
+}
+
+// Picks the first of `name`, `name.2`, `name.3`... that is free in both
+// files/ and info/, and reserves it by writing its .trashinfo file
+fn reserve_names(
+    files: &Path,
+    info: &Path,
+    name: &OsStr,
+    original: &Path,
+) -> Result<(OsString, PathBuf), ScanError> {
+    for attempt in 1u32.. {
+        let mut candidate = name.to_os_string();
+        if attempt > 1 {
+            candidate.push(format!(".{}", attempt));
+        }
+        // Left behind by an earlier trashing that lost its .trashinfo
+        if fs::symlink_metadata(files.join(&candidate)).is_ok() {
+            continue;
+        }
+        let mut info_name = candidate.clone();
+        info_name.push(".trashinfo");
+        let info_path = info.join(&info_name);
+
+        match OpenOptions::new().write(true).create_new(true).open(&info_path) {
+            Ok(mut file) => {
+                let contents = format!(
+                    "[Trash Info]\nPath={}\nDeletionDate={}\n",
+                    percent_encode(original.as_os_str().as_bytes()),
+                    deletion_date()
+                );
+                file.write_all(contents.as_bytes()).map_err(|e| ScanError::io(&info_path, &e))?;
+                return Ok((candidate, info_path));
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(ScanError::io(&info_path, &e)),
+        }
+    }
+    unreachable!("ran out of trash names")
+}
+
+// A rename that fails rather than replace whatever took the name since it
+// was reserved. Filesystems without RENAME_NOREPLACE get a plain rename, the
+// name having been checked free.
+fn rename_noreplace(from: &Path, to: &Path) -> io::Result<()> {
+    let from_c = CString::new(from.as_os_str().as_bytes())?;
+    let to_c = CString::new(to.as_os_str().as_bytes())?;
+    let renamed = unsafe {
+        libc::renameat2(libc::AT_FDCWD, from_c.as_ptr(), libc::AT_FDCWD, to_c.as_ptr(), libc::RENAME_NOREPLACE)
+    };
+    if renamed == 0 {
+        return Ok(());
+    }
+    let error = io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::EINVAL) => fs::rename(from, to),
+        _ => Err(error),
+    }
+}
+
+// The spec stores Path= URL-escaped, which keeps non-UTF-8 bytes intact
+fn percent_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+// Local time as YYYY-MM-DDThh:mm:ss
+fn deletion_date() -> String {
+    let mut now: libc::time_t = 0;
+    let mut local: libc::tm = unsafe { std::mem::zeroed() };
+    unsafe {
+        libc::time(&mut now);
+        libc::localtime_r(&now, &mut local);
+    }
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        local.tm_year + 1900,
+        local.tm_mon + 1,
+        local.tm_mday,
+        local.tm_hour,
+        local.tm_min,
+        local.tm_sec
+    )
+}
@@ -1,10 +1,13 @@
 import React, { useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { toFileSystemError } from '@/services/FileSystemService';
+import type { PathArg } from '@/utils/paths';
 
 interface ContextMenuProps {
   x: number;
   y: number;
   path: string;
+  rawPath?: string;
   isDir: boolean;
   onClose: () => void;
   onNavigate?: () => void;
@@ -14,11 +17,14 @@ export const ContextMenu: React.FC<ContextMenuProps> = ({
   x, 
   y, 
   path, 
+  rawPath,
   isDir, 
   onClose,
   onNavigate
 }) => {
   const menuRef = useRef<HTMLDivElement>(null);
+  // Commands get the exact bytes when the display path is lossy
+  const pathArg: PathArg = rawPath ? { raw: rawPath } : path;
 
   // Close menu when clicking outside
   useEffect(() => {
@@ -46,22 +52,34 @@ export const ContextMenu: React.FC<ContextMenuProps> = ({
 
   const handleShowInFinder = async () => {
     try {
-      await invoke('show_in_finder', { path });
+      await invoke('show_in_finder', { path: pathArg });
       onClose();
     } catch (error) {
-      console.error('Failed to show in Finder:', error);
+      const failure = toFileSystemError(error);
+      console.error('Failed to show in Finder:', failure.message);
+      if (failure.kind === 'notFound') {
+        // The item is already gone, so the current view is stale
+        window.location.reload();
+      }
     }
   };
 
   const handleMoveToTrash = async () => {
     try {
-      await invoke('move_to_trash', { path });
+      await invoke('move_to_trash', { path: pathArg });
       onClose();
       // Trigger a rescan of the parent directory
       window.location.reload();
     } catch (error) {
-      console.error('Failed to move to trash:', error);
-      alert(`Failed to move to trash: ${error}`);
+      const failure = toFileSystemError(error);
+      console.error('Failed to move to trash:', failure.message);
+      if (failure.kind === 'notFound') {
+        // Nothing left to trash; refresh so the view drops the item
+        onClose();
+        window.location.reload();
+        return;
+      }
+      alert(`Failed to move to trash: ${failure.message}`);
     }
   };
 
@@ -26,8 +26,10 @@ export const ScanProgress: React.FC<ScanProgressComponentProps> = ({ onCancel })
         <div className="progress-info">
           {progress ? (
             <>
-              <p className="current-path">{progress.current_path}</p>
-              <p className="items-count">Processed: {progress.items_processed} items</p>
+              <p className="current-path">{progress.currentDirectory}</p>
+              <p className="items-count">
+                Processed: {progress.filesSeen + progress.directoriesSeen} items
+              </p>
             </>
           ) : (
             <p>Starting scan...</p>
@@ -187,14 +187,15 @@ export const ScanProgressTreemap: React.FC<ScanProgressTreemapProps> = ({ onCanc
     const setupListener = async () => {
       try {
         unlistenFn = await listen<ScanProgress>('scan-progress', (event) => {
-          const { current_path, items_processed } = event.payload;
+          const { currentDirectory, filesSeen, directoriesSeen } = event.payload;
+          const items_processed = filesSeen + directoriesSeen;
           
           setStats({ 
             items: items_processed, 
-            currentPath: current_path.split('/').pop() || 'root'
+            currentPath: currentDirectory.split('/').pop() || 'root'
           });
 
-          processPath(current_path);
+          processPath(currentDirectory);
           
           // Update visualization less frequently to prevent lockup
           if (items_processed % 100 === 0 || Date.now() - lastUpdateRef.current > 1000) {
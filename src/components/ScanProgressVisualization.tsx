@@ -104,11 +104,12 @@ export const ScanProgressVisualization: React.FC<ScanProgressVisualizationProps>
 
     // Listen for scan progress
     const unlisten = listen<ScanProgress>('scan-progress', (event) => {
-      const { current_path, items_processed } = event.payload;
+      const { currentDirectory, filesSeen, directoriesSeen } = event.payload;
+      const items_processed = filesSeen + directoriesSeen;
       setItemsProcessed(items_processed);
 
       // Parse the path and build tree structure
-      const parts = current_path.split('/').filter(Boolean);
+      const parts = currentDirectory.split('/').filter(Boolean);
       let currentNode = treeDataRef.current;
 
       // Build path hierarchy
@@ -1,5 +1,6 @@
 import React, { useEffect, useState, useRef } from 'react';
 import { listen } from '@tauri-apps/api/event';
+import type { ScanProgress } from '@/schemas/FileSystem';
 
 interface StatusBarProps {
   currentPath?: string;
@@ -7,7 +8,7 @@ interface StatusBarProps {
 }
 
 interface ScanSummary {
-  mdfindPasses: number;
+  passes: number;
   totalFilesFound: number;
   lastScanTime?: Date;
 }
@@ -17,11 +18,11 @@ export const StatusBar: React.FC<StatusBarProps> = ({ currentPath, totalSize })
   const [isScanning, setIsScanning] = useState(false);
   const [showTooltip, setShowTooltip] = useState(false);
   const [scanSummary, setScanSummary] = useState<ScanSummary>({
-    mdfindPasses: 0,
+    passes: 0,
     totalFilesFound: 0
   });
   const summaryRef = useRef<ScanSummary>({
-    mdfindPasses: 0,
+    passes: 0,
     totalFilesFound: 0
   });
 
@@ -32,40 +33,27 @@ export const StatusBar: React.FC<StatusBarProps> = ({ currentPath, totalSize })
     const setupListeners = async () => {
       try {
         // Listen for scan progress
-        unlistenProgress = await listen<{ current_path: string; items_processed: number }>('scan-progress', (event) => {
-          const { current_path, items_processed } = event.payload;
+        unlistenProgress = await listen<ScanProgress>('scan-progress', (event) => {
+          const { phase, backend, filesSeen, directoriesSeen, pass, totalPasses } = event.payload;
           setIsScanning(true);
-          
-          // Extract scan type from the message
-          if (current_path.includes('mdfind')) {
-            setScanStatus(current_path);
-            // Count mdfind passes
-            const passMatch = current_path.match(/pass (\d+)\/(\d+)/);
-            if (passMatch) {
-              summaryRef.current.mdfindPasses = parseInt(passMatch[2]);
-            }
-            // Extract file count from mdfind status
-            const fileMatch = current_path.match(/Found (\d+) large files/);
-            if (fileMatch) {
-              summaryRef.current.totalFilesFound = parseInt(fileMatch[1]);
-            }
-          } else if (current_path.includes('du')) {
-            setScanStatus(current_path);
-            // Extract file count from du status
-            const duMatch = current_path.match(/Found (\d+) items/);
-            if (duMatch) {
-              summaryRef.current.totalFilesFound = Math.max(
-                summaryRef.current.totalFilesFound, 
-                parseInt(duMatch[1])
-              );
-            }
-          } else if (current_path.includes('Building directory tree')) {
+
+          if (totalPasses !== null) {
+            summaryRef.current.passes = totalPasses;
+          }
+          summaryRef.current.totalFilesFound = Math.max(
+            summaryRef.current.totalFilesFound,
+            filesSeen
+          );
+
+          if (phase === 'buildingTree') {
             setScanStatus('Building directory tree...');
+          } else if (pass !== null && totalPasses !== null) {
+            setScanStatus(
+              `${backend} pass ${pass}/${totalPasses}: Found ${filesSeen.toLocaleString()} files`
+            );
           } else {
-            setScanStatus(`Scanning: ${items_processed} items`);
-            summaryRef.current.totalFilesFound = Math.max(
-              summaryRef.current.totalFilesFound, 
-              items_processed
+            setScanStatus(
+              `${backend}: ${filesSeen.toLocaleString()} files, ${directoriesSeen.toLocaleString()} folders`
             );
           }
         });
@@ -148,8 +136,8 @@ export const StatusBar: React.FC<StatusBarProps> = ({ currentPath, totalSize })
                 <div className="tooltip-arrow"></div>
                 <h4>Last Scan Summary</h4>
                 <div className="tooltip-row">
-                  <span className="tooltip-label">mdfind passes:</span>
-                  <span className="tooltip-value">{scanSummary.mdfindPasses || 0}</span>
+                  <span className="tooltip-label">Passes:</span>
+                  <span className="tooltip-value">{scanSummary.passes || 0}</span>
                 </div>
                 <div className="tooltip-row">
                   <span className="tooltip-label">Files found:</span>
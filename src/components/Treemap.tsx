@@ -4,6 +4,9 @@ import type { FileNode } from '@/schemas/FileSystem';
 import { formatBytes } from '@/utils/format';
 import { ContextMenu } from './ContextMenu';
 
+// Dotfiles flagged by a scan with hidden: 'dim' are drawn faded
+const HIDDEN_OPACITY = 0.45;
+
 interface TreemapProps {
   data: FileNode;
   width: number;
@@ -165,6 +168,22 @@ export const Treemap: React.FC<TreemapProps> = ({
       const isDir = node.data.isDir;
       const depth = node.depth;
       
+      // Entries the scan could not read are grey
+      if (node.data.inaccessible) {
+        return '#9a9a9a';
+      }
+      
+      // Mount points a scan did not cross are dark grey
+      if (node.data.fsType) {
+        return '#5a5a5a';
+      }
+      
+      // Aggregates of the smallest entries, space a preview has not placed
+      // yet and directories' own entries are light grey
+      if (node.data.otherCount || node.data.unattributed || node.data.ownBlocks) {
+        return '#d4d4d4';
+      }
+      
       // Files are white
       if (!isDir) {
         return '#ffffff';
@@ -223,7 +242,8 @@ export const Treemap: React.FC<TreemapProps> = ({
     
     parentContainers.forEach(parent => {
       const group = svg.append('g')
-        .attr('class', 'parent-node');
+        .attr('class', 'parent-node')
+        .style('opacity', parent.data.hidden ? HIDDEN_OPACITY : 1);
       
       // Draw parent rectangle
       group.append('rect')
@@ -290,6 +310,8 @@ export const Treemap: React.FC<TreemapProps> = ({
       })
       .on('contextmenu', (event, d) => {
         event.preventDefault();
+        // Aggregate nodes are not real files to act on
+        if (d.data.otherCount || d.data.unattributed || d.data.ownBlocks) return;
         setContextMenu({
           x: event.clientX,
           y: event.clientY,
@@ -304,7 +326,8 @@ export const Treemap: React.FC<TreemapProps> = ({
         return;
       }
       
-      const group = d3.select(this);
+      const group = d3.select(this)
+        .style('opacity', d.data.hidden ? HIDDEN_OPACITY : 1);
       const width = d.x1 - d.x0;
       const height = d.y1 - d.y0;
       const isFile = !d.data.isDir;
@@ -459,7 +482,25 @@ export const Treemap: React.FC<TreemapProps> = ({
         const parentSize = d.parent ? d.parent.value || 0 : hierarchy.value || 0;
         const percentage = parentSize > 0 ? ((d.value || 0) / parentSize * 100).toFixed(1) : '0';
         const path = d.ancestors().reverse().map(a => a.data.name).join(' → ');
-        return `${d.data.name}\n${formatBytes(d.data.size)}\n${percentage}% of parent\nPath: ${path}`;
+        const unreadable = d.data.inaccessible ? '\nCould not be read completely' : '';
+        if (d.data.fsType) {
+          return `${d.data.name}\nMount point (${d.data.fsType}), not scanned\nPath: ${path}`;
+        }
+        if (d.data.unattributed) {
+          return `${d.data.name}\n${formatBytes(d.data.size)} not yet attributed while exact totals are computed\n${percentage}% of parent`;
+        }
+        if (d.data.ownBlocks) {
+          return `${d.data.name}\n${formatBytes(d.data.size)} taken by the directory's own listing\n${percentage}% of parent`;
+        }
+        if (d.data.otherCount) {
+          return `${d.data.name}\n${formatBytes(d.data.size)} in total\n${percentage}% of parent`;
+        }
+        const sizes = `${formatBytes(d.data.apparentSize)} apparent, ${formatBytes(d.data.allocatedSize)} on disk`;
+        const excluded = d.data.collapsed ? '\nExcluded, shown collapsed' : '';
+        const link = d.data.symlinkTarget ? `\nLink to ${d.data.symlinkTarget}` : '';
+        const counted = d.data.countedAt ? `\nContents counted under ${d.data.countedAt}` : '';
+        const shared = d.data.sharedSize ? `\n${formatBytes(d.data.sharedSize)} shared via hard links` : '';
+        return `${d.data.name}${link}${counted}\n${sizes}${shared}\n${percentage}% of parent\nPath: ${path}${unreadable}${excluded}`;
       });
 
   }, [data, width, height, onNodeClick, selectedNode]);
@@ -477,6 +518,7 @@ export const Treemap: React.FC<TreemapProps> = ({
           x={contextMenu.x}
           y={contextMenu.y}
           path={contextMenu.node.path}
+          rawPath={contextMenu.node.rawPath}
           isDir={contextMenu.node.isDir}
           onClose={() => setContextMenu(null)}
           onNavigate={contextMenu.node.isDir && onNodeClick ? () => {
@@ -1,31 +1,34 @@
 import { useQuery, UseQueryResult, useQueryClient } from '@tanstack/react-query';
 import { Effect } from 'effect';
-import { FileSystemService, FileSystemServiceLive } from '@/services/FileSystemService';
-import type { FileNode } from '@/schemas/FileSystem';
-import { useEffect } from 'react';
+import { FileSystemService, FileSystemServiceLive, toFileSystemError } from '@/services/FileSystemService';
+import type { ScanIntermediate, ScanResult } from '@/schemas/FileSystem';
+import { useEffect, useRef } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
+import { toPathArg } from '@/utils/paths';
 
-// Cancel any ongoing scans
-async function cancelOngoingScans() {
+// Cancel a scan started by this hook, if it is still running
+async function cancelScan(scanId: string | null) {
+  if (!scanId) return;
   try {
-    await invoke('cancel_scan');
-    console.log('[useFileSystem] Cancelled ongoing scans');
+    await invoke('cancel_scan', { id: scanId });
+    console.log('[useFileSystem] Cancelled scan', scanId);
   } catch (error) {
-    console.error('[useFileSystem] Failed to cancel scans:', error);
+    console.error('[useFileSystem] Failed to cancel scan:', toFileSystemError(error).message);
   }
 }
 
-export function useScanDirectory(path: string | null): UseQueryResult<FileNode, Error> {
+export function useScanDirectory(path: string | null): UseQueryResult<ScanResult, Error> {
   const queryClient = useQueryClient();
   const queryKey = ['scan', path];
+  const scanIdRef = useRef<string | null>(null);
   
   // Listen for intermediate scan results and cancel previous scans
   useEffect(() => {
     if (!path) return;
     
-    // Cancel any ongoing scans when path changes
-    cancelOngoingScans();
+    // Cancel the previous path's scan when path changes
+    cancelScan(scanIdRef.current);
     
     // Clear any cached query data for this path to ensure fresh start
     queryClient.removeQueries({ queryKey });
@@ -34,16 +37,19 @@ export function useScanDirectory(path: string | null): UseQueryResult<FileNode,
     
     const setupListener = async () => {
       try {
-        unlistenFn = await listen<FileNode>('scan-intermediate', (event) => {
+        unlistenFn = await listen<ScanIntermediate>('scan-intermediate', (event) => {
+          // Ignore results from other scans
+          if (event.payload?.scanId !== scanIdRef.current) return;
+          const root = event.payload.root;
           console.log('[useFileSystem] Received intermediate scan result:', {
-            name: event.payload?.name,
-            childrenCount: event.payload?.children?.length || 0,
-            size: event.payload?.size
+            name: root?.name,
+            childrenCount: root?.children?.length || 0,
+            size: root?.size
           });
           // Update the query data with intermediate results
-          queryClient.setQueryData(queryKey, (oldData: FileNode | undefined) => {
+          queryClient.setQueryData(queryKey, (oldData: ScanResult | undefined) => {
             console.log('[useFileSystem] Updating query data with intermediate results');
-            return event.payload || oldData;
+            return root ? { root, complete: false, incompleteReason: null, skipped: [], excludedMounts: [] } : oldData;
           });
         });
       } catch (error) {
@@ -65,13 +71,15 @@ export function useScanDirectory(path: string | null): UseQueryResult<FileNode,
     queryFn: async ({ signal }) => {
       if (!path) throw new Error('No path provided');
       
-      // Cancel any ongoing scans before starting new one
-      await cancelOngoingScans();
+      // Cancel this hook's previous scan before starting a new one
+      await cancelScan(scanIdRef.current);
+      const scanId = crypto.randomUUID();
+      scanIdRef.current = scanId;
       
-      console.log('[useFileSystem] Starting scan for path:', path);
+      console.log('[useFileSystem] Starting scan for path:', path, 'id:', scanId);
       
       const program = FileSystemService.pipe(
-        Effect.flatMap((service) => service.scan(path)),
+        Effect.flatMap((service) => service.scan(toPathArg(path), undefined, scanId)),
         Effect.provide(FileSystemServiceLive)
       );
       
@@ -79,11 +87,13 @@ export function useScanDirectory(path: string | null): UseQueryResult<FileNode,
       const result = await Effect.runPromise(program);
       
       console.log('[useFileSystem] Scan completed. Result:', {
-        name: result?.name,
-        path: result?.path,
-        size: result?.size,
-        isDir: result?.isDir,
-        childrenCount: result?.children?.length || 0
+        name: result.root.name,
+        path: result.root.path,
+        size: result.root.size,
+        isDir: result.root.isDir,
+        childrenCount: result.root.children?.length || 0,
+        complete: result.complete,
+        incompleteReason: result.incompleteReason
       });
       console.log('[useFileSystem] Full result:', result);
       
@@ -7,6 +7,7 @@ import { ScanProgressTreemap } from '@/components/ScanProgressTreemap';
 import { Breadcrumbs } from '@/components/Breadcrumbs';
 import { StatusBar } from '@/components/StatusBar';
 import type { FileNode } from '@/schemas/FileSystem';
+import { displayPath, nodePathParam, parentPathParam } from '@/utils/paths';
 
 export const ScanView: React.FC = () => {
   const { path } = useParams({ from: '/scan/$path' });
@@ -59,26 +60,27 @@ export const ScanView: React.FC = () => {
     hasData: !!data, 
     error: error?.message,
     dataDetails: data ? {
-      name: data.name,
-      path: data.path,
-      size: data.size,
-      childrenCount: data.children?.length || 0
+      name: data.root.name,
+      path: data.root.path,
+      size: data.root.size,
+      childrenCount: data.root.children?.length || 0,
+      complete: data.complete
     } : null
   });
 
   const handleNodeClick = (node: FileNode) => {
     if (node && node.isDir) {
       // Navigate to the new directory path
-      const encodedPath = encodeURIComponent(node.path);
+      const encodedPath = encodeURIComponent(nodePathParam(node));
       navigate({ to: '/scan/$path', params: { path: encodedPath } });
     }
   };
 
   const handleBack = () => {
-    // Navigate back to parent directory or home
-    const pathParts = decodedPath.split('/').filter(Boolean);
-    if (pathParts.length > 1) {
-      const parentPath = '/' + pathParts.slice(0, -1).join('/');
+    // Navigate back to parent directory or home. The scanned root is the
+    // resolved path, so "~/x" or "./x" still have a parent.
+    const parentPath = parentPathParam(data ? nodePathParam(data.root) : decodedPath);
+    if (parentPath) {
       const encodedParentPath = encodeURIComponent(parentPath);
       navigate({ to: '/scan/$path', params: { path: encodedParentPath } });
     } else {
@@ -101,7 +103,7 @@ export const ScanView: React.FC = () => {
     );
   }
 
-  const displayData = data;
+  const displayData = data?.root;
   
   console.log('[ScanView] Rendering results view with data:', {
     hasDisplayData: !!displayData,
@@ -114,10 +116,37 @@ export const ScanView: React.FC = () => {
       <nav>
         <button onClick={handleBack} className="back-button">← Back</button>
         <div className="header-content">
-          <Breadcrumbs currentPath={decodedPath} />
+          <Breadcrumbs currentPath={displayPath(decodedPath)} />
         </div>
       </nav>
       
+      {data && !data.complete && data.incompleteReason && (
+        <div className="scan-incomplete">
+          {data.incompleteReason === 'cancelled'
+            ? 'Scan was cancelled. Showing the partial results found so far.'
+            : 'Scan hit its time budget. Showing the partial results found so far.'}
+        </div>
+      )}
+      
+      {data && data.skipped.length > 0 && (
+        <div className="scan-incomplete" title={data.skipped.map((s) => `${s.path}: ${s.message}`).join('\n')}>
+          {data.skipped.length === 1
+            ? `1 path could not be read (${data.skipped[0].path}), so the total may be too small.`
+            : `${data.skipped.length} paths could not be read, so the total may be too small.`}
+        </div>
+      )}
+      
+      {data && data.excludedMounts.length > 0 && (
+        <div
+          className="scan-incomplete"
+          title={data.excludedMounts.map((m) => `${m.path} (${m.fsType}, ${m.kind})`).join('\n')}
+        >
+          {data.excludedMounts.length === 1
+            ? `Skipped the ${data.excludedMounts[0].fsType} mount at ${data.excludedMounts[0].path}.`
+            : `Skipped ${data.excludedMounts.length} pseudo or network mounts.`}
+        </div>
+      )}
+      
       {displayData && (
         <div className="treemap-container">
           <Treemap
@@ -4,8 +4,27 @@ import { Schema } from '@effect/schema';
 const BaseFileNode = Schema.Struct({
   name: Schema.String,
   path: Schema.String,
+  // Base64 OS bytes, set only when `path` is a lossy rendering
+  rawPath: Schema.optional(Schema.String),
   size: Schema.Number,
+  apparentSize: Schema.Number,
+  allocatedSize: Schema.Number,
+  sharedSize: Schema.optional(Schema.Number),
   isDir: Schema.Boolean,
+  inaccessible: Schema.optional(Schema.Boolean),
+  fsType: Schema.optional(Schema.String),
+  symlinkTarget: Schema.optional(Schema.String),
+  // Where a directory reached again through a symlink was counted; it has no size
+  countedAt: Schema.optional(Schema.String),
+  collapsed: Schema.optional(Schema.Boolean),
+  hidden: Schema.optional(Schema.Boolean),
+  // Number of entries folded into an "Other" node (1 when a tool only said
+  // there were some); such nodes have no path
+  otherCount: Schema.optional(Schema.Number),
+  // Part of a directory a large-file preview has not placed yet
+  unattributed: Schema.optional(Schema.Boolean),
+  // Blocks of a directory's own entry, so its children add up to its size
+  ownBlocks: Schema.optional(Schema.Boolean),
 });
 
 // Define the recursive type
@@ -17,18 +36,190 @@ export interface FileNode extends Schema.Schema.Type<typeof BaseFileNode> {
 export const FileNodeSchema = Schema.Struct({
   name: Schema.String,
   path: Schema.String,
+  rawPath: Schema.optional(Schema.String),
   size: Schema.Number,
+  apparentSize: Schema.Number,
+  allocatedSize: Schema.Number,
+  sharedSize: Schema.optional(Schema.Number),
   isDir: Schema.Boolean,
+  inaccessible: Schema.optional(Schema.Boolean),
+  fsType: Schema.optional(Schema.String),
+  symlinkTarget: Schema.optional(Schema.String),
+  countedAt: Schema.optional(Schema.String),
+  collapsed: Schema.optional(Schema.Boolean),
+  hidden: Schema.optional(Schema.Boolean),
+  otherCount: Schema.optional(Schema.Number),
+  unattributed: Schema.optional(Schema.Boolean),
+  ownBlocks: Schema.optional(Schema.Boolean),
   children: Schema.optional(Schema.Unknown),
 }) as unknown as Schema.Schema<FileNode>;
 
+export const ScanPhaseSchema = Schema.Literal('starting', 'scanning', 'buildingTree', 'finished');
+
+export type ScanPhase = Schema.Schema.Type<typeof ScanPhaseSchema>;
+
 export const ScanProgressSchema = Schema.Struct({
-  current_path: Schema.String,
-  items_processed: Schema.Number,
+  scanId: Schema.String,
+  phase: ScanPhaseSchema,
+  backend: Schema.String,
+  filesSeen: Schema.Number,
+  directoriesSeen: Schema.Number,
+  bytesAccumulated: Schema.Number,
+  errors: Schema.Number,
+  currentDirectory: Schema.String,
+  elapsedMs: Schema.Number,
+  pass: Schema.NullOr(Schema.Number),
+  totalPasses: Schema.NullOr(Schema.Number),
 });
 
 export type ScanProgress = Schema.Schema.Type<typeof ScanProgressSchema>;
 
+export const IncompleteReasonSchema = Schema.Literal('cancelled', 'timeBudget');
+
+export type IncompleteReason = Schema.Schema.Type<typeof IncompleteReasonSchema>;
+
+export const SkipReasonSchema = Schema.Literal('permissionDenied', 'loop', 'vanished', 'io');
+
+export type SkipReason = Schema.Schema.Type<typeof SkipReasonSchema>;
+
+export const SkippedPathSchema = Schema.Struct({
+  path: Schema.String,
+  reason: SkipReasonSchema,
+  code: Schema.NullOr(Schema.Number),
+  message: Schema.String,
+});
+
+export type SkippedPath = Schema.Schema.Type<typeof SkippedPathSchema>;
+
+export const MountKindSchema = Schema.Literal('real', 'pseudo', 'network');
+
+export type MountKind = Schema.Schema.Type<typeof MountKindSchema>;
+
+export const ExcludedMountSchema = Schema.Struct({
+  path: Schema.String,
+  fsType: Schema.String,
+  source: Schema.String,
+  kind: MountKindSchema,
+});
+
+export type ExcludedMount = Schema.Schema.Type<typeof ExcludedMountSchema>;
+
+export const ScanResultSchema = Schema.Struct({
+  root: FileNodeSchema,
+  complete: Schema.Boolean,
+  incompleteReason: Schema.NullOr(IncompleteReasonSchema),
+  skipped: Schema.Array(SkippedPathSchema),
+  excludedMounts: Schema.Array(ExcludedMountSchema),
+});
+
+export type ScanResult = Schema.Schema.Type<typeof ScanResultSchema>;
+
+export const ScanErrorKindSchema = Schema.Literal(
+  'notFound',
+  'permissionDenied',
+  'cancelled',
+  'backendUnavailable',
+  'timeout',
+  'alreadyRunning',
+  'unknownScan',
+  'unsupported',
+  'io'
+);
+
+export type ScanErrorKind = Schema.Schema.Type<typeof ScanErrorKindSchema>;
+
+// Error payload rejected by scan_path, show_in_finder, move_to_trash,
+// cancel_scan and get_scan_status
+export const ScanErrorSchema = Schema.Struct({
+  kind: ScanErrorKindSchema,
+  path: Schema.String,
+  code: Schema.NullOr(Schema.Number),
+  backend: Schema.optional(Schema.String),
+  limitMs: Schema.optional(Schema.Number),
+  scanId: Schema.optional(Schema.String),
+  operation: Schema.optional(Schema.String),
+  message: Schema.optional(Schema.String),
+});
+
+export type ScanError = Schema.Schema.Type<typeof ScanErrorSchema>;
+
+export interface ScanIntermediate {
+  scanId: string;
+  root: FileNode;
+}
+
+export const BackendKindSchema = Schema.Literal('native', 'du', 'dust', 'mdfind');
+
+export type BackendKind = Schema.Schema.Type<typeof BackendKindSchema>;
+
+export const BackendInfoSchema = Schema.Struct({
+  kind: BackendKindSchema,
+  name: Schema.String,
+  available: Schema.Boolean,
+  capabilities: Schema.Struct({
+    fullDepth: Schema.Boolean,
+    apparentSize: Schema.Boolean,
+    allocatedSize: Schema.Boolean,
+    streaming: Schema.Boolean,
+  }),
+});
+
+export type BackendInfo = Schema.Schema.Type<typeof BackendInfoSchema>;
+
+// Which byte count drives `size`: logical length or blocks used on disk
+export const SizeMetricSchema = Schema.Literal('apparent', 'allocated');
+
+export type SizeMetric = Schema.Schema.Type<typeof SizeMetricSchema>;
+
+// Which symlinks a scan descends through
+export const SymlinkPolicySchema = Schema.Literal('never', 'withinRoot', 'always');
+
+export type SymlinkPolicy = Schema.Schema.Type<typeof SymlinkPolicySchema>;
+
+// What happens to a subtree an exclusion rule matches
+export const ExcludeModeSchema = Schema.Literal('skip', 'collapse');
+
+export type ExcludeMode = Schema.Schema.Type<typeof ExcludeModeSchema>;
+
+// Glob in gitignore syntax
+export interface ExcludeRule {
+  pattern: string;
+  mode?: ExcludeMode;
+}
+
+// Dotfiles are shown by default; 'dim' flags them for de-emphasis
+export const HiddenEntriesSchema = Schema.Literal('show', 'dim', 'hide');
+
+export type HiddenEntries = Schema.Schema.Type<typeof HiddenEntriesSchema>;
+
+export interface ScanOptions {
+  backend?: BackendKind;
+  // Levels below the root (2 when unset)
+  depth?: number;
+  // Entries kept per directory, largest first (100 when unset)
+  maxChildren?: number;
+  // Entries smaller than this many bytes are left out
+  minSize?: number;
+  // Report every level and entry, ignoring the three limits above
+  completeTree?: boolean;
+  timeoutMs?: number;
+  timeBudgetMs?: number;
+  sizeMetric?: SizeMetric;
+  // Stop at mount points; on by default when scanning '/'
+  oneFilesystem?: boolean;
+  includePseudoFilesystems?: boolean;
+  includeNetworkFilesystems?: boolean;
+  symlinkPolicy?: SymlinkPolicy;
+  exclude?: ExcludeRule[];
+  ignoreFile?: { path: string; mode?: ExcludeMode };
+  cachedirTag?: ExcludeMode;
+  hidden?: HiddenEntries;
+  // Threads for the native walk; one per CPU when unset
+  threads?: number;
+  // Follow an mdfind preview with a native walk for exact totals; off by default
+  backfill?: boolean;
+}
+
 export const DiskInfoSchema = Schema.Struct({
   name: Schema.String,
   path: Schema.String,
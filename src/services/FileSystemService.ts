@@ -1,18 +1,52 @@
-import { Effect, Context, Layer } from 'effect';
+import { Effect, Context, Layer, Option } from 'effect';
 import { Schema } from '@effect/schema';
 import { invoke } from '@tauri-apps/api/core';
-import { FileNodeSchema, type FileNode } from '@/schemas/FileSystem';
+import {
+  BackendInfoSchema,
+  ScanErrorKindSchema,
+  ScanErrorSchema,
+  ScanResultSchema,
+  type BackendInfo,
+  type ScanOptions,
+  type ScanResult,
+} from '@/schemas/FileSystem';
+import { formatScanError } from '@/utils/format';
+import type { PathArg } from '@/utils/paths';
 
 export class FileSystemError extends Schema.TaggedError<FileSystemError>()(
   'FileSystemError',
   {
     message: Schema.String,
+    // Set when the backend rejected with a typed ScanError
+    kind: Schema.optional(ScanErrorKindSchema),
+    path: Schema.optional(Schema.String),
+    code: Schema.optional(Schema.NullOr(Schema.Number)),
   }
 ) {}
 
+// Converts a rejected invoke into a FileSystemError, keeping the error kind,
+// path and OS error code when the backend sent a ScanError
+export function toFileSystemError(error: unknown): FileSystemError {
+  return Option.match(Schema.decodeUnknownOption(ScanErrorSchema)(error), {
+    onNone: () => new FileSystemError({ message: String(error) }),
+    onSome: (scanError) =>
+      new FileSystemError({
+        message: formatScanError(scanError),
+        kind: scanError.kind,
+        path: scanError.path,
+        code: scanError.code,
+      }),
+  });
+}
+
 export interface FileSystemService {
-  readonly scan: (path: string) => Effect.Effect<FileNode, FileSystemError>;
+  readonly scan: (
+    path: PathArg,
+    options?: ScanOptions,
+    scanId?: string
+  ) => Effect.Effect<ScanResult, FileSystemError>;
   readonly getHomeDirectory: () => Effect.Effect<string, FileSystemError>;
+  readonly listBackends: () => Effect.Effect<readonly BackendInfo[], FileSystemError>;
 }
 
 export const FileSystemService = Context.GenericTag<FileSystemService>(
@@ -22,13 +56,13 @@ export const FileSystemService = Context.GenericTag<FileSystemService>(
 export const FileSystemServiceLive = Layer.succeed(
   FileSystemService,
   FileSystemService.of({
-    scan: (path: string) =>
+    scan: (path: PathArg, options?: ScanOptions, scanId?: string) =>
       Effect.tryPromise({
-        try: () => invoke<unknown>('scan_path', { path }),
-        catch: (error) => new FileSystemError({ message: String(error) }),
+        try: () => invoke<unknown>('scan_path', { path, options, scanId }),
+        catch: toFileSystemError,
       }).pipe(
         Effect.flatMap((data) =>
-          Schema.decodeUnknown(FileNodeSchema)(data).pipe(
+          Schema.decodeUnknown(ScanResultSchema)(data).pipe(
             Effect.mapError((error) => 
               new FileSystemError({ message: error.message })
             )
@@ -41,5 +75,17 @@ export const FileSystemServiceLive = Layer.succeed(
         try: () => invoke<string>('get_home_directory'),
         catch: (error) => new FileSystemError({ message: String(error) }),
       }),
+
+    listBackends: () =>
+      Effect.tryPromise({
+        try: () => invoke<unknown>('list_scan_backends'),
+        catch: (error) => new FileSystemError({ message: String(error) }),
+      }).pipe(
+        Effect.flatMap((data) =>
+          Schema.decodeUnknown(Schema.Array(BackendInfoSchema))(data).pipe(
+            Effect.mapError((error) => new FileSystemError({ message: error.message }))
+          )
+        )
+      ),
   })
 );
\ No newline at end of file
@@ -1,5 +1,5 @@
 import { describe, it, expect } from 'vitest';
-import { formatBytes } from './format';
+import { formatBytes, formatScanError } from './format';
 
 describe('formatBytes', () => {
   it('should format bytes correctly', () => {
@@ -13,4 +13,33 @@ describe('formatBytes', () => {
     expect(formatBytes(1536)).toBe('1.5 KB');
     expect(formatBytes(2621440)).toBe('2.5 MB');
   });
-});
\ No newline at end of file
+});
+
+describe('formatScanError', () => {
+  it('should describe each error kind', () => {
+    expect(formatScanError({ kind: 'notFound', path: '/missing', code: 2 })).toBe(
+      '/missing does not exist'
+    );
+    expect(formatScanError({ kind: 'permissionDenied', path: '/root', code: 13 })).toBe(
+      'Permission denied: /root'
+    );
+    expect(formatScanError({ kind: 'timeout', path: '/usr', code: null, limitMs: 1500 })).toBe(
+      'Scan of /usr timed out after 1.5s'
+    );
+    expect(formatScanError({ kind: 'unknownScan', path: '', code: null, scanId: 'scan-7' })).toBe(
+      'Unknown scan: scan-7'
+    );
+    expect(
+      formatScanError({ kind: 'io', path: '/dev/sda', code: 5, message: 'Input/output error' })
+    ).toBe('/dev/sda: Input/output error');
+    expect(
+      formatScanError({
+        kind: 'unsupported',
+        path: '/',
+        code: null,
+        operation: 'Skipping excluded entries',
+        backend: 'du',
+      })
+    ).toBe('Skipping excluded entries is not supported by the du backend');
+  });
+});
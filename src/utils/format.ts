@@ -1,3 +1,5 @@
+import type { ScanError } from '@/schemas/FileSystem';
+
 export function formatBytes(bytes: number): string {
   if (bytes === 0) return '0 B';
   
@@ -8,6 +10,31 @@ export function formatBytes(bytes: number): string {
   return `${(bytes / Math.pow(k, i)).toFixed(1)} ${sizes[i]}`;
 }
 
+export function formatScanError(error: ScanError): string {
+  switch (error.kind) {
+    case 'notFound':
+      return `${error.path} does not exist`;
+    case 'permissionDenied':
+      return `Permission denied: ${error.path}`;
+    case 'cancelled':
+      return `Scan of ${error.path} was cancelled`;
+    case 'backendUnavailable':
+      return `Scan backend '${error.backend ?? 'unknown'}' is not available`;
+    case 'timeout':
+      return `Scan of ${error.path} timed out after ${((error.limitMs ?? 0) / 1000).toFixed(1)}s`;
+    case 'alreadyRunning':
+      return `Scan '${error.scanId ?? ''}' is already running`;
+    case 'unknownScan':
+      return `Unknown scan: ${error.scanId ?? ''}`;
+    case 'unsupported':
+      return error.backend
+        ? `${error.operation ?? 'This action'} is not supported by the ${error.backend} backend`
+        : `${error.operation ?? 'This action'} is not supported on this platform`;
+    case 'io':
+      return `${error.path}: ${error.message ?? 'I/O error'}`;
+  }
+}
+
 export function formatDate(date: Date | string): string {
   const d = typeof date === 'string' ? new Date(date) : date;
   return d.toLocaleDateString() + ' ' + d.toLocaleTimeString();
@@ -0,0 +1,26 @@
+import { describe, it, expect } from 'vitest';
+import { displayPath, nodePathParam, parentPathParam, toPathArg } from './paths';
+
+// "/tmp/bad\xff/f\xfe" and its parent "/tmp/bad\xff"
+const RAW_FILE = 'L3RtcC9iYWT/L2b+';
+const RAW_DIR = 'L3RtcC9iYWT/';
+
+describe('path params', () => {
+  it('should round-trip plain and raw paths', () => {
+    expect(nodePathParam({ path: '/tmp/a' })).toBe('/tmp/a');
+    expect(toPathArg('/tmp/a')).toBe('/tmp/a');
+    const param = nodePathParam({ path: '/tmp/bad�/f�', rawPath: RAW_FILE });
+    expect(toPathArg(param)).toEqual({ raw: RAW_FILE });
+  });
+
+  it('should find the parent by bytes', () => {
+    expect(parentPathParam('/tmp/a')).toBe('/tmp');
+    expect(parentPathParam('/tmp')).toBeNull();
+    expect(parentPathParam(`raw:${RAW_FILE}`)).toBe(`raw:${RAW_DIR}`);
+    expect(parentPathParam(`raw:${RAW_DIR}`)).toBe('/tmp');
+  });
+
+  it('should display raw paths lossily', () => {
+    expect(displayPath(`raw:${RAW_DIR}`)).toBe('/tmp/bad�');
+  });
+});
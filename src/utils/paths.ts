@@ -0,0 +1,52 @@
+import type { FileNode } from '@/schemas/FileSystem';
+
+// A path as the backend commands accept it: plain text, or the base64 OS
+// bytes of a path that is not valid UTF-8 (FileNode.rawPath)
+export type PathArg = string | { raw: string };
+
+// Route params carry raw paths behind this prefix so they survive navigation
+const RAW_PREFIX = 'raw:';
+
+// The /scan/$path param (before URL encoding) that rescans `node`
+export function nodePathParam(node: Pick<FileNode, 'path' | 'rawPath'>): string {
+  return node.rawPath ? RAW_PREFIX + node.rawPath : node.path;
+}
+
+export function toPathArg(param: string): PathArg {
+  return param.startsWith(RAW_PREFIX) ? { raw: param.slice(RAW_PREFIX.length) } : param;
+}
+
+function decodeRaw(raw: string): Uint8Array {
+  return Uint8Array.from(atob(raw), (c) => c.charCodeAt(0));
+}
+
+function encodeRaw(bytes: Uint8Array): string {
+  return btoa(String.fromCharCode(...bytes));
+}
+
+// The route param of the parent directory, or null at the top level. Raw
+// paths are split on the '/' byte so the parent keeps its exact bytes too.
+export function parentPathParam(param: string): string | null {
+  if (param.startsWith(RAW_PREFIX)) {
+    const bytes = decodeRaw(param.slice(RAW_PREFIX.length));
+    let end = bytes.length;
+    while (end > 1 && bytes[end - 1] === 0x2f) end--;
+    const slash = bytes.lastIndexOf(0x2f, end - 1);
+    if (slash <= 0) return null;
+    const parent = bytes.slice(0, slash);
+    // A parent that is valid UTF-8 goes back to a plain path
+    try {
+      return new TextDecoder('utf-8', { fatal: true }).decode(parent);
+    } catch {
+      return RAW_PREFIX + encodeRaw(parent);
+    }
+  }
+  const parts = param.split('/').filter(Boolean);
+  return parts.length > 1 ? '/' + parts.slice(0, -1).join('/') : null;
+}
+
+// Human-readable form of a route param, with undecodable bytes shown as U+FFFD
+export function displayPath(param: string): string {
+  if (!param.startsWith(RAW_PREFIX)) return param;
+  return new TextDecoder().decode(decodeRaw(param.slice(RAW_PREFIX.length)));
+}